@@ -0,0 +1,261 @@
+use crate::error::Error;
+use crate::runner::{self, Timings};
+use crate::solution::Params;
+use std::collections::HashMap;
+use std::fs;
+use std::time::Duration;
+
+/// Default fraction by which a median time must increase to be flagged as a
+/// regression
+pub const REGRESSION_THRESHOLD:f64 = 0.1;
+
+/// Phases of a solution run that are benchmarked
+pub const PHASES:[&str;3] = ["parse", "part1", "part2"];
+
+/// Summary statistics over a set of timing samples
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub min:Duration,
+    pub median:Duration,
+    pub mean:Duration,
+    /// Sample standard deviation
+    pub std_dev:Duration
+}
+impl Stats {
+    /// Compute the statistics for the given `samples`, which must not be
+    /// empty
+    pub fn from_samples(samples:&Vec<Duration>) -> Stats {
+        assert!(!samples.is_empty(), "No samples to compute statistics from");
+        let mut sorted = samples.clone();
+        sorted.sort();
+        let n = sorted.len();
+        let median = if n % 2 == 1 {
+            sorted[n / 2]
+        }
+        else {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / 2
+        };
+        let mean_secs = sorted.iter().map(|d| d.as_secs_f64()).sum::<f64>() / n as f64;
+        let mut variance = 0.0;
+        if n > 1 {
+            variance = sorted.iter().map(|d| (d.as_secs_f64() - mean_secs).powi(2)).sum::<f64>() / (n - 1) as f64;
+        }
+        return Stats {
+            min:sorted[0],
+            median,
+            mean:Duration::from_secs_f64(mean_secs),
+            std_dev:Duration::from_secs_f64(variance.sqrt())
+        };
+    }
+}
+
+/// Benchmark statistics for each phase of one day's solution
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchResult {
+    pub day:usize,
+    pub runs:usize,
+    /// Statistics for each of `PHASES`, in order
+    pub phases:Vec<Stats>
+}
+
+/// Run the given `day` with solver `params` on the input `lines` once to warm up, then `runs`
+/// more times, collecting timing statistics for each phase. Returns the error
+/// if the solution fails on the input. Panics if `runs` is zero.
+pub fn bench_day(day:usize, lines:&Vec<String>, runs:usize, params:&Params) -> Result<BenchResult, Error> {
+    assert!(runs > 0, "Need at least one run to benchmark");
+    // Warm-up run, discarded
+    runner::run_day(day, "", lines, params)?;
+    let mut samples:Vec<Vec<Duration>> = vec![Vec::new(); PHASES.len()];
+    for _ in 0..runs {
+        let timings:Timings = runner::run_day(day, "", lines, params)?.timings;
+        samples[0].push(timings.parse);
+        samples[1].push(timings.part1);
+        samples[2].push(timings.part2);
+    }
+    let phases = samples.iter().map(Stats::from_samples).collect();
+    return Ok(BenchResult { day, runs, phases });
+}
+
+/// Save the benchmark `results` to the file at `path`, one line per day and
+/// phase with the timings in nanoseconds
+pub fn save(path:&str, results:&Vec<BenchResult>) -> Result<(), Error> {
+    let mut contents = String::from("# day phase runs min_ns median_ns mean_ns std_dev_ns\n");
+    for result in results {
+        for i in 0..PHASES.len() {
+            let stats = result.phases[i];
+            contents.push_str(&format!("{} {} {} {} {} {} {}\n", result.day, PHASES[i], result.runs,
+                stats.min.as_nanos(), stats.median.as_nanos(), stats.mean.as_nanos(), stats.std_dev.as_nanos()));
+        }
+    }
+    match fs::write(path, contents) {
+        Ok(_) => return Ok(()),
+        Err(e) => return Err(Error::Io(format!("Unable to write benchmark results to {}: {}", path, e)))
+    }
+}
+
+/// Load benchmark results previously written by `save`, keyed by day. Each
+/// day needs a line for every phase, all with the same number of runs.
+pub fn load(path:&str) -> Result<HashMap<usize, BenchResult>, Error> {
+    let contents = match fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(_) => return Err(Error::Io(format!("Unable to open file at path {}", path)))
+    };
+    let malformed = |index:usize| Error::Input(format!("malformed benchmark results in {} at line {}", path, index + 1));
+    let mut results:HashMap<usize, (usize, Vec<Option<Stats>>)> = HashMap::new();
+    for (index, line) in contents.lines().enumerate() {
+        if line.starts_with("#") || line.trim().is_empty() {
+            continue;
+        }
+        let parts:Vec<&str> = line.split_whitespace().collect();
+        let numbers:Vec<u64> = parts.iter().skip(2).filter_map(|x| x.parse::<u64>().ok()).collect();
+        let day = parts[0].parse::<usize>();
+        let phase = PHASES.iter().position(|&p| parts.get(1) == Some(&p));
+        if parts.len() != 7 || numbers.len() != 5 || day.is_err() || phase.is_none() || numbers[0] == 0 {
+            return Err(malformed(index));
+        }
+        let runs = numbers[0] as usize;
+        let (day_runs, phases) = results.entry(day.unwrap()).or_insert((runs, vec![None; PHASES.len()]));
+        if *day_runs != runs {
+            return Err(malformed(index));
+        }
+        phases[phase.unwrap()] = Some(Stats {
+            min:Duration::from_nanos(numbers[1]),
+            median:Duration::from_nanos(numbers[2]),
+            mean:Duration::from_nanos(numbers[3]),
+            std_dev:Duration::from_nanos(numbers[4])
+        });
+    }
+    let mut loaded = HashMap::new();
+    for (day, (runs, phases)) in results {
+        match phases.into_iter().collect::<Option<Vec<Stats>>>() {
+            Some(phases) => loaded.insert(day, BenchResult { day, runs, phases }),
+            None => return Err(Error::Input(format!("benchmark results in {} are missing a phase of day {}", path, day)))
+        };
+    }
+    return Ok(loaded);
+}
+
+/// Relative change in median time from `previous` to `current`, as a fraction
+/// of the previous median
+pub fn median_change(previous:&Stats, current:&Stats) -> f64 {
+    let prev = previous.median.as_secs_f64();
+    if prev == 0.0 {
+        return 0.0;
+    }
+    return (current.median.as_secs_f64() - prev) / prev;
+}
+
+/// Whether any phase of the benchmark `results` has a median time more than
+/// `threshold` (a fraction of the previous median) slower than in `previous`
+pub fn has_regression(results:&Vec<BenchResult>, previous:&HashMap<usize, BenchResult>, threshold:f64) -> bool {
+    return results.iter().any(|result| (0..PHASES.len()).any(|i| {
+        return previous.get(&result.day).is_some_and(|prev| median_change(&prev.phases[i], &result.phases[i]) > threshold);
+    }));
+}
+
+/// Format the benchmark `results` as a table. If `previous` results are given,
+/// the previous median and its number of runs and the change in median time
+/// are shown for each phase, and changes slower than `threshold` (a fraction
+/// of the previous median) are flagged as regressions.
+pub fn summary_table(results:&Vec<BenchResult>, previous:Option<&HashMap<usize, BenchResult>>, threshold:f64) -> String {
+    let mut headers = vec!["Day", "Phase", "Min", "Median", "Mean", "Std dev"];
+    if previous.is_some() {
+        headers.push("Previous");
+        headers.push("Change");
+    }
+    let mut rows:Vec<Vec<String>> = Vec::new();
+    for result in results {
+        for i in 0..PHASES.len() {
+            let stats = result.phases[i];
+            let mut row = vec![result.day.to_string(), PHASES[i].to_string(), format!("{:?}", stats.min),
+                format!("{:?}", stats.median), format!("{:?}", stats.mean), format!("{:?}", stats.std_dev)];
+            if let Some(prev) = previous {
+                match prev.get(&result.day) {
+                    Some(prev_result) => {
+                        let change = median_change(&prev_result.phases[i], &stats);
+                        row.push(format!("{:?} ({} runs)", prev_result.phases[i].median, prev_result.runs));
+                        if change > threshold {
+                            row.push(format!("{:+.1}% REGRESSION", change * 100.0));
+                        }
+                        else {
+                            row.push(format!("{:+.1}%", change * 100.0));
+                        }
+                    },
+                    None => {
+                        row.push("-".to_string());
+                        row.push("-".to_string());
+                    }
+                }
+            }
+            rows.push(row);
+        }
+    }
+    let headers:Vec<String> = headers.iter().map(|h| h.to_string()).collect();
+    return runner::format_table(&headers, &rows);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(samples:&[u64]) -> Vec<Duration> {
+        return samples.iter().map(|&s| Duration::from_millis(s)).collect();
+    }
+    fn close(a:Duration, b:f64) -> bool {
+        return (a.as_secs_f64() - b).abs() < 1e-9;
+    }
+    fn result(day:usize, medians:&[u64]) -> BenchResult {
+        return BenchResult { day, runs:1, phases:medians.iter().map(|&m| Stats::from_samples(&ms(&[m]))).collect() };
+    }
+
+    #[test]
+    fn stats() {
+        let stats = Stats::from_samples(&ms(&[4, 1, 10, 3, 2]));
+        assert_eq!((stats.min, stats.median), (Duration::from_millis(1), Duration::from_millis(3)));
+        assert!(close(stats.mean, 0.004));
+        // Squared deviations from the mean sum to 50 over 4 degrees of freedom
+        assert!(close(stats.std_dev, (12.5f64).sqrt() / 1000.0));
+        assert_eq!(Stats::from_samples(&ms(&[4, 1, 3, 2])).median, Duration::from_micros(2500));
+        let single = Stats::from_samples(&ms(&[7]));
+        assert_eq!(single, Stats { min:Duration::from_millis(7), median:Duration::from_millis(7), mean:Duration::from_millis(7), std_dev:Duration::ZERO });
+    }
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir().join(format!("adventofcode2025-bench-{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        let results = vec![BenchResult { day:3, runs:5, phases:vec![Stats::from_samples(&ms(&[4, 1, 10, 3, 2])), Stats::from_samples(&ms(&[7])), Stats::from_samples(&ms(&[1, 2]))] }];
+        save(path, &results).unwrap();
+        let loaded = load(path);
+        fs::remove_file(path).unwrap();
+        // Saved to the nanosecond, which is as precise as a Duration
+        assert_eq!(loaded.unwrap(), HashMap::from([(3, results[0].clone())]));
+        assert!(matches!(load(path), Err(Error::Io(_))));
+        // Each day needs every phase, all over the same number of runs
+        for contents in ["3 parse 5 1 1 1 0\n", "3 parse 5 1 1 1 0\n3 part1 5 1 1 1 0\n3 part2 4 1 1 1 0\n", "3 lex 5 1 1 1 0\n"] {
+            fs::write(path, contents).unwrap();
+            let loaded = load(path);
+            fs::remove_file(path).unwrap();
+            assert!(matches!(loaded, Err(Error::Input(_))));
+        }
+    }
+    #[test]
+    fn regressions() {
+        let previous = HashMap::from([(1, result(1, &[100, 100, 100]))]);
+        assert!((median_change(&previous[&1].phases[0], &Stats::from_samples(&ms(&[125]))) - 0.25).abs() < 1e-9);
+        assert_eq!(median_change(&Stats::from_samples(&ms(&[0])), &Stats::from_samples(&ms(&[125]))), 0.0);
+        // 5% slower is within the threshold, 20% slower isn't, and day 2 has
+        // nothing to compare with
+        let steady = vec![result(1, &[105, 90, 100]), result(2, &[500, 500, 500])];
+        let table = summary_table(&steady, Some(&previous), REGRESSION_THRESHOLD);
+        assert!(table.contains("+5.0%") && table.contains("-10.0%"));
+        assert_eq!(table.matches("100ms (1 runs)").count(), 3);
+        assert!(!table.contains("REGRESSION"));
+        assert!(!has_regression(&steady, &previous, REGRESSION_THRESHOLD));
+        let slower = vec![result(1, &[100, 120, 100])];
+        let table = summary_table(&slower, Some(&previous), REGRESSION_THRESHOLD);
+        assert_eq!(table.matches("REGRESSION").count(), 1);
+        assert!(table.contains("+20.0% REGRESSION"));
+        assert!(has_regression(&slower, &previous, REGRESSION_THRESHOLD));
+        assert!(!summary_table(&slower, None, REGRESSION_THRESHOLD).contains("Change"));
+    }
+}
@@ -1,32 +1,86 @@
-
 use crate::util;
+use crate::error::{self, Error};
+use crate::solution::{Answer, Solution};
 
-/// Print the solutions to day 1 for the given input `lines`
-pub fn run(lines:&Vec<String>) {
-    let mut pos = 50;
-    let mut part1 = 0;
-    let mut part2 = 0;
-    for i in 0..lines.len() {
-        let (prefix, num_str) = lines[i].split_at(1);
-        let number = num_str.parse::<i128>().unwrap();
-        // For the positive direction, the number of times the zero is crossed 
-        // is simple division to complement the modular remainder for the 
-        // dial position.
-        if prefix == "R" {
-            part2 += (pos + number) / 100;
-            pos = (pos + number) % 100;
+pub struct Day1 {
+    /// Dial rotations as (direction is right, distance)
+    rotations:Vec<(bool, i128)>
+}
+impl Solution for Day1 {
+    fn parse(lines:&Vec<String>) -> Result<Day1, Error> {
+        let mut rotations = Vec::new();
+        for i in 0..lines.len() {
+            let right = match lines[i].chars().nth(0) {
+                Some('R') => true,
+                Some('L') => false,
+                _ => return Err(Error::parse_at(i, 0, "expected rotation direction L or R"))
+            };
+            let number = error::parse_field::<i128>(&lines[i][1..], i, 1)?;
+            if number < 0 {
+                return Err(Error::parse_at(i, 1, "rotation distance must be non-negative"));
+            }
+            rotations.push((right, number));
         }
-        // The negative direction is more complicated, but treating the dial as
-        // if it were inverted to count the zero crossings in the positive 
-        // direction simplifies things
-        else {
-            part2 += (((100 - pos) % 100) + number) / 100;
-            pos = util::abs_mod(pos - number, 100);
+        return Ok(Day1 { rotations });
+    }
+    fn part1(&self) -> Result<Answer, Error> {
+        let mut pos = 50;
+        let mut part1:usize = 0;
+        for &(right, number) in self.rotations.iter() {
+            if right {
+                pos = (pos + number) % 100;
+            }
+            else {
+                pos = util::abs_mod(pos - number, 100);
+            }
+            if pos == 0 {
+                part1 += 1;
+            }
         }
-        if pos == 0 {
-            part1 += 1;
+        return Ok(Answer::from(part1));
+    }
+    fn part2(&self) -> Result<Answer, Error> {
+        let mut pos = 50;
+        let mut part2 = 0;
+        for &(right, number) in self.rotations.iter() {
+            // For the positive direction, the number of times the zero is 
+            // crossed is simple division to complement the modular remainder 
+            // for the dial position.
+            if right {
+                part2 += (pos + number) / 100;
+                pos = (pos + number) % 100;
+            }
+            // The negative direction is more complicated, but treating the 
+            // dial as if it were inverted to count the zero crossings in the 
+            // positive direction simplifies things
+            else {
+                part2 += (((100 - pos) % 100) + number) / 100;
+                pos = util::abs_mod(pos - number, 100);
+            }
         }
+        return Ok(Answer::from(part2));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Day1 {
+        let lines = util::read_lines(include_str!("../testdata/day1.txt").as_bytes()).unwrap();
+        return Day1::parse(&lines).unwrap();
+    }
+    #[test]
+    fn part1_example() {
+        assert_eq!(example().part1(), Ok(Answer::Int(3)));
+    }
+    #[test]
+    fn part2_example() {
+        assert_eq!(example().part2(), Ok(Answer::Int(6)));
+    }
+    #[test]
+    fn invalid_direction() {
+        let lines = vec!["L68".to_string(), "X30".to_string()];
+        assert_eq!(Day1::parse(&lines).err(), Some(Error::parse_at(1, 0, "expected rotation direction L or R")));
     }
-    println!("Part 1: {}", part1);
-    println!("Part 2: {}", part2);
 }
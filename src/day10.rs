@@ -1,301 +1,612 @@
 
-use crate::util::{self, abs_mod, Rational};
-use std::cmp::{min, max};
-use std::collections::HashMap;
+use crate::util::{BigInt, BitMatrix, BitVec, Integer, LinearProgram, LpSolution, Matrix, MinCostSolution, Rational, Relation, Scalar};
+use crate::error::Error;
+use crate::solution::{Answer, Solution};
 
-#[derive(Debug)]
+/// Exact numbers for solving the joltage system
+type Q = Rational<i128>;
+/// Largest cost of a button press
+const MAX_COST:usize = u32::MAX as usize;
+
+#[derive(Debug, PartialEq)]
 struct Machine {
     pub indicator:Vec<isize>,
     pub buttons:Vec<Vec<isize>>,
+    /// The cost of each press of each button, 1 unless annotated
+    pub costs:Vec<usize>,
     pub joltages:Vec<isize>
 }
 impl Machine {
-    /// Find either a unique solution to the system of linear equations for the
-    /// indicators or joltages or the minimal one.
-    pub fn rref_solve(&self, part1:bool) -> usize {
-        let rows = self.indicator.len();
-        let cols = self.buttons.len() + 1; 
-        // build augmented matrix
-        let mut augmented_matrix:Vec<Vec<Rational>> = Vec::new();
-        for i in 0..rows {
-            let mut row: Vec<Rational> = Vec::new();
-            for j in 0..self.buttons.len() {
-                if self.buttons[j].contains(&(i as isize)) {
-                    row.push(Rational::new_int(1));
-                }
-                else {
-                     row.push(Rational::new_int(0));
-                }
+    /// The matrix of the joltage system, with a column per button that is 1
+    /// in the rows of the lights the button is wired to
+    fn button_matrix<T: Scalar>(&self) -> Matrix<T> {
+        let mut matrix = Matrix::new(self.indicator.len(), self.buttons.len());
+        for j in 0..self.buttons.len() {
+            for &light in &self.buttons[j] {
+                matrix[(light as usize, j)] = T::one();
             }
-            if part1 {
-                row.push(Rational::new_int(self.indicator[i]));
+        }
+        return matrix;
+    }
+    /// Find the cheapest button presses that set the indicator lights, as
+    /// whether each button is pressed. Each button toggles its lights, so
+    /// this is a system over GF(2), and pressing a button twice does nothing.
+    /// Reports if no combination of button presses matches the lights.
+    pub fn indicator_presses(&self) -> Result<MinCostSolution, Error> {
+        let target = BitVec::from_bools(&self.indicator.iter().map(|&x| x == 1).collect::<Vec<bool>>());
+        return self.indicator_matrix().min_cost_solution(&target, &self.costs);
+    }
+    /// The matrix of the indicator light system over GF(2)
+    fn indicator_matrix(&self) -> BitMatrix {
+        let mut matrix = BitMatrix::new(self.indicator.len(), self.buttons.len());
+        for j in 0..self.buttons.len() {
+            for &light in &self.buttons[j] {
+                matrix.set(light as usize, j, true);
             }
-            else {
-                row.push(Rational::new_int(self.joltages[i]));
+        }
+        return matrix;
+    }
+    /// Find the cheapest button presses that reach the joltages, where each
+    /// press adds 1 to the joltage of each of the button's lights, as the
+    /// number of presses of each button. This is an integer program, solved
+    /// exactly by branch and bound over `i128`, or over `BigInt` if that
+    /// overflows. Returns `None` if no combination of button presses reaches
+    /// the joltages.
+    pub fn joltage_presses(&self) -> Result<Option<Vec<usize>>, Error> {
+        match self.joltage_presses_in::<i128>() {
+            Err(Error::Overflow(_)) => return self.joltage_presses_in::<BigInt>(),
+            result => return result
+        }
+    }
+    /// Solve the joltage integer program over rationals of `I`, returning
+    /// `Error::Overflow` if the arithmetic doesn't fit, or `Error::NoSolution`
+    /// if the program is unbounded, which only a solver bug could cause
+    fn joltage_presses_in<I: Integer>(&self) -> Result<Option<Vec<usize>>, Error> {
+        let matrix = self.button_matrix::<Rational<I>>();
+        let mut program = LinearProgram::new(self.costs.iter().map(|&c| Rational::new_int(I::from_i64(c as i64))).collect());
+        for i in 0..matrix.rows() {
+            program.constrain(matrix.row(i).to_vec(), Relation::Equal, Rational::new_int(I::from_i64(self.joltages[i] as i64)));
+        }
+        match program.solve_integer()?.0 {
+            LpSolution::Optimal { x, .. } => {
+                let presses = x.iter().map(|presses| presses.num.to_i128().and_then(|p| usize::try_from(p).ok())).collect::<Option<Vec<usize>>>();
+                return presses.map(Some).ok_or(Error::Overflow("the number of button presses does not fit in usize".to_string()));
+            },
+            LpSolution::Infeasible => return Ok(None),
+            // Costs are never negative, so pressing buttons can't make the
+            // cost arbitrarily small
+            LpSolution::Unbounded => return Err(Error::NoSolution("the joltage program has no cheapest presses".to_string()))
+        }
+    }
+    /// The total cost of pressing each button the number of times in
+    /// `presses`, or `None` if it overflows
+    fn cost(&self, presses:&[usize]) -> Option<usize> {
+        let mut total:usize = 0;
+        for j in 0..presses.len() {
+            total = total.checked_add(presses[j].checked_mul(self.costs[j])?)?;
+        }
+        return Some(total);
+    }
+    /// The number of times each button is pressed for the indicator lights
+    fn press_counts(&self, presses:&BitVec) -> Vec<usize> {
+        return (0..self.buttons.len()).map(|j| presses.get(j) as usize).collect();
+    }
+    /// The indicator lights after pressing the buttons in `presses` once
+    /// each, starting from all off
+    fn lights_after(&self, presses:&BitVec) -> Vec<isize> {
+        let mut lights = vec![0; self.indicator.len()];
+        for j in presses.ones() {
+            for &light in &self.buttons[j] {
+                lights[light as usize] ^= 1;
             }
-            augmented_matrix.push(row);
         }
-        // row reduce augmented matrix down
-        for pivot_index in 0..cols-1 {
-            if pivot_index >= rows {
-                break;
+        return lights;
+    }
+    /// The joltages after pressing each button the number of times in
+    /// `presses`, starting from zero, or `None` if they overflow
+    fn joltages_after(&self, presses:&[usize]) -> Option<Vec<isize>> {
+        let mut joltages:Vec<isize> = vec![0; self.joltages.len()];
+        for j in 0..presses.len() {
+            let count = isize::try_from(presses[j]).ok()?;
+            for &light in &self.buttons[j] {
+                joltages[light as usize] = joltages[light as usize].checked_add(count)?;
             }
-            // swap row to one with non-zero entry in pivot column if needed
-            if augmented_matrix[pivot_index][pivot_index] == 0 {
-                for r in pivot_index..augmented_matrix.len() {
-                    if augmented_matrix[r][pivot_index] != 0 {
-                        Machine::swap_rows(&mut augmented_matrix, pivot_index, r);
-                        break;
-                    }
+        }
+        return Some(joltages);
+    }
+    /// Describe how both systems are solved: the rank and free buttons of
+    /// each, then the presses found and the state they produce checked
+    /// against the target, or that there are no such presses
+    fn explain(&self) -> String {
+        let free_buttons = |pivots:Vec<usize>| {
+            let free:Vec<String> = (0..self.buttons.len()).filter(|j| !pivots.contains(j)).map(|j| j.to_string()).collect();
+            return format!("rank {}, free buttons {}", pivots.len(), if free.is_empty() { "none".to_string() } else { free.join(", ") });
+        };
+        let verdict = |matches:bool| if matches { "ok" } else { "MISMATCH" };
+        let mut report = format!("  Indicator lights: {}\n", free_buttons(self.indicator_matrix().pivots()));
+        match self.indicator_presses() {
+            Ok(MinCostSolution::Optimal(presses)) => {
+                let lights = self.lights_after(&presses);
+                let pressed:Vec<String> = presses.ones().map(|j| j.to_string()).collect();
+                report += &format!("    press buttons {} ({})\n", if pressed.is_empty() { "none".to_string() } else { pressed.join(", ") }, self.total(&self.press_counts(&presses)));
+                report += &format!("    lights {}, target {}: {}\n", Machine::format_lights(&lights), Machine::format_lights(&self.indicator), verdict(lights == self.indicator));
+            },
+            Ok(MinCostSolution::Inconsistent) => report += "    infeasible: no button presses match the lights\n",
+            Err(e) => report += &format!("    {}\n", e)
+        }
+        report += &format!("  Joltages: {}\n", free_buttons(self.button_matrix::<Q>().rref().1));
+        match self.joltage_presses() {
+            Ok(Some(presses)) => {
+                let counts:Vec<String> = presses.iter().map(|p| p.to_string()).collect();
+                report += &format!("    presses per button {} ({})\n", counts.join(" "), self.total(&presses));
+                match self.joltages_after(&presses) {
+                    Some(joltages) => report += &format!("    joltages {}, target {}: {}\n", Machine::format_joltages(&joltages), Machine::format_joltages(&self.joltages), verdict(joltages == self.joltages)),
+                    None => report += &format!("    joltages don't fit in {} bits, target {}: {}\n", isize::BITS, Machine::format_joltages(&self.joltages), verdict(false))
                 }
-            }
-            Machine::scale_row(&mut augmented_matrix, pivot_index);
-            if augmented_matrix[pivot_index][pivot_index] != 1 {
-                continue;
-            }
-            assert!(augmented_matrix[pivot_index][pivot_index] == 1, "Row reduction error: pivot position {} not 1 ({})", pivot_index, augmented_matrix[pivot_index][pivot_index]);
-            Machine::row_replacement_down(&mut augmented_matrix, pivot_index, part1);
+            },
+            Ok(None) => report += "    infeasible: no button presses reach the joltages\n",
+            Err(e) => report += &format!("    {}\n", e)
         }
-        // row reduce augmented matrix up
-        for pivot_index in 1..cols {
-            if pivot_index >= rows {
-                break;
-            }
-            if augmented_matrix[pivot_index][pivot_index] != 1 {
-                continue;
+        return report;
+    }
+    /// The total presses in `presses`, and their cost if any button has an
+    /// annotated cost
+    fn total(&self, presses:&[usize]) -> String {
+        let total = match presses.iter().try_fold(0usize, |sum, &p| sum.checked_add(p)) {
+            Some(sum) => format!("{} in total", sum),
+            None => format!("more than {} in total", usize::MAX)
+        };
+        if self.costs.iter().all(|&c| c == 1) {
+            return total;
+        }
+        match self.cost(presses) {
+            Some(cost) => return format!("{}, costing {}", total, cost),
+            None => return format!("{}, costing more than {}", total, usize::MAX)
+        }
+    }
+    /// The error for a total cost too large to count, reached at the machine
+    /// on the line with index `line`
+    fn overflow(line:usize) -> Error {
+        return Error::Overflow(format!("the total cost of the presses up to the machine on line {} does not fit in {} bits", line + 1, usize::BITS));
+    }
+    fn format_lights(lights:&[isize]) -> String {
+        return format!("[{}]", lights.iter().map(|&x| if x == 1 { '#' } else { '.' }).collect::<String>());
+    }
+    fn format_joltages(joltages:&[isize]) -> String {
+        return format!("{{{}}}", joltages.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(","));
+    }
+}
+
+/// Position in a machine line being parsed, for `Machine::parse_line`
+struct Cursor {
+    chars:Vec<char>,
+    pos:usize,
+    line:usize
+}
+impl Cursor {
+    fn peek(&self) -> Option<char> {
+        return self.chars.get(self.pos).copied();
+    }
+    fn error(&self, column:usize, message:&str) -> Error {
+        return Error::parse_at(self.line, column, message);
+    }
+    /// An error at the current position that `expected` was wanted here
+    fn unexpected(&self, expected:&str) -> Error {
+        let found = match self.peek() {
+            Some(c) => format!("'{}'", c),
+            None => "the end of the line".to_string()
+        };
+        return self.error(self.pos, &format!("expected {}, found {}", expected, found));
+    }
+    /// Skip over spaces, returning whether there were any
+    fn skip_spaces(&mut self) -> bool {
+        let start = self.pos;
+        while self.peek() == Some(' ') {
+            self.pos += 1;
+        }
+        return self.pos > start;
+    }
+    /// Read a non-negative integer, returning it with its column
+    fn number(&mut self) -> Result<(isize, usize), Error> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return Err(self.unexpected("a number"));
+        }
+        let digits:String = self.chars[start..self.pos].iter().collect();
+        match digits.parse::<isize>() {
+            Ok(n) => return Ok((n, start)),
+            Err(_) => return Err(self.error(start, &format!("number {} is too large", digits)))
+        }
+    }
+    /// Read a comma separated list of numbers between `open` and `close`,
+    /// returning each number with its column
+    fn list(&mut self, open:char, close:char) -> Result<Vec<(isize, usize)>, Error> {
+        if self.peek() != Some(open) {
+            return Err(self.unexpected(&format!("'{}'", open)));
+        }
+        self.pos += 1;
+        let mut numbers = Vec::new();
+        if self.peek() == Some(close) {
+            self.pos += 1;
+            return Ok(numbers);
+        }
+        loop {
+            numbers.push(self.number()?);
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(c) if c == close => {
+                    self.pos += 1;
+                    return Ok(numbers);
+                },
+                _ => return Err(self.unexpected(&format!("',' or '{}'", close)))
             }
-            Machine::row_replacement_up(&mut augmented_matrix, pivot_index, part1);
-        }
-        // scale each row to integers
-        for r in 0..rows {
-            Machine::re_scale_row(&mut augmented_matrix, r);
-        }
-        
-        let mut free_variables = Vec::new();
-        for col in 0..self.buttons.len() {
-            let non_zero_entries = augmented_matrix.iter().filter(|&row| row[col] != 0).count();
-            if non_zero_entries != 1 {
-                free_variables.push(col);
+        }
+    }
+}
+
+impl Machine {
+    /// Parse the machine on the line with 0-based index `line`, in the
+    /// format
+    ///
+    /// ```text
+    /// machine  = lights (" " button)* " " joltages
+    /// lights   = "[" ("." | "#")* "]"
+    /// button   = "(" [light ("," light)*] ")" [":" cost]
+    /// joltages = "{" [number ("," number)*] "}"
+    /// ```
+    ///
+    /// where tokens may be separated by any number of spaces, each button
+    /// lists distinct lights, and there is a joltage for every light
+    fn parse_line(text:&str, line:usize) -> Result<Machine, Error> {
+        let mut cursor = Cursor { chars:text.chars().collect(), pos:0, line };
+        cursor.skip_spaces();
+        if cursor.peek() != Some('[') {
+            return Err(cursor.unexpected("'[' to start the indicator lights"));
+        }
+        cursor.pos += 1;
+        let mut indicator = Vec::new();
+        loop {
+            match cursor.peek() {
+                Some('.') => indicator.push(0),
+                Some('#') => indicator.push(1),
+                Some(']') => break,
+                _ => return Err(cursor.unexpected("an indicator light '.' or '#', or ']'"))
             }
+            cursor.pos += 1;
         }
-        let free_variable_max = if part1 { 1 } else {*self.joltages.iter().max().unwrap() as usize };
-        // If a unique solution, return the sum of the augmented column
-        if free_variables.len() == 0 {
-            return augmented_matrix.iter().map(|row| row.last().unwrap().num as usize).sum();
-        }
-        // Otherwise, try all combinations of free variables and return the minimal number of button presses
-        let mut assignments = vec![0;free_variables.len()];
-        let minimal_presses = self.try_variables(&augmented_matrix, &free_variables, free_variable_max, 0, &mut assignments, part1);
-        assert!(!minimal_presses.is_none(), "Error on machine {:?} - no solution found", self);
-        return minimal_presses.unwrap();
-    }
-    /// Recursively try all combinations of button presses up to `free_variable_max` on the free variables, and return the 
-    /// minimal solution found where the number of presses for each button is non-negative
-    fn try_variables(&self, matrix:&Vec<Vec<Rational>>, free_variables:&Vec<usize>, free_variable_max:usize, index:usize, assignments:&mut Vec<usize>, part1:bool) -> Option<usize> {
-        if index == free_variables.len() {
-            let mut button_sum = 0;
-            let mut free_variable_map = HashMap::new();
-            for i in 0..free_variables.len() {
-                free_variable_map.insert(free_variables[i], i);
+        cursor.pos += 1;
+        let mut buttons = Vec::new();
+        let mut costs = Vec::new();
+        loop {
+            if !cursor.skip_spaces() && cursor.peek().is_some() {
+                return Err(cursor.unexpected("a space"));
             }
-            let mut outputs = vec![0 as isize;self.joltages.len()];
-            for i in 0..self.buttons.len() {
-                if free_variable_map.contains_key(&i) {
-                    button_sum += assignments[*free_variable_map.get(&i).unwrap()];
-                    for j in 0..self.buttons[i].len() {
-                        if part1 {
-                            outputs[self.buttons[i][j] as usize] = abs_mod(outputs[self.buttons[i][j] as usize] as i128 + assignments[*free_variable_map.get(&i).unwrap()] as i128, 2) as isize;
+            match cursor.peek() {
+                Some('(') => {
+                    let mut button = Vec::new();
+                    for (light, column) in cursor.list('(', ')')? {
+                        if light as usize >= indicator.len() {
+                            return Err(cursor.error(column, &format!("button wired to light {} of a machine with {} lights", light, indicator.len())));
                         }
-                        else {
-                            outputs[self.buttons[i][j] as usize] += assignments[*free_variable_map.get(&i).unwrap()] as isize;
+                        if button.contains(&light) {
+                            return Err(cursor.error(column, &format!("button wired to light {} twice", light)));
                         }
+                        button.push(light);
                     }
-                    continue;
-                }
-                // subtract free variables from augmented column
-                let mut presses = matrix[i].last().unwrap().clone();
-                for j in 0..free_variables.len() {
-                    presses -= matrix[i][free_variables[j]] * Rational::new_int(assignments[j] as isize);
-                }
-                // If the pivot entry isn't 1, divide the result
-                if !part1 {
-                    presses /= matrix[i][i];
-                }
-                // Presses for each button must be non-negative and non-fractional
-                if presses.denom != 1 {
-                    return None;
-                }
-                if part1 {
-                    presses.num = abs_mod(presses.num as i128, 2) as isize;
-                }
-                if presses < 0 || presses.denom != 1 {
-                    //println!("presses {} on button {} for assignments {:?}", presses, i, assignments);
-                    return None;
-                }
-                assert!(presses.denom == 1, "Error on try_variables: presses {}", presses);
-                button_sum += presses.num as usize;
-                for j in 0..self.buttons[i].len() {
-                    if part1 {
-                        outputs[self.buttons[i][j] as usize] = (outputs[self.buttons[i][j] as usize] + presses.num) % 2;
+                    buttons.push(button);
+                    if cursor.peek() == Some(':') {
+                        cursor.pos += 1;
+                        let (cost, column) = cursor.number()?;
+                        if cost as usize > MAX_COST {
+                            return Err(cursor.error(column, &format!("button cost {} is more than {}", cost, MAX_COST)));
+                        }
+                        costs.push(cost as usize);
                     }
                     else {
-                        outputs[self.buttons[i][j] as usize] += presses.num;
+                        costs.push(1);
                     }
-                }
-            }
-            if part1 {
-                if outputs != self.indicator {
-                    return None;
-                }
-            }
-            else {
-                if outputs != self.joltages {
-                    return None;
-                }
-            }
-            return Some(button_sum);
-        }
-        let mut min_button_presses = 0;
-        for i in 0..=free_variable_max {
-            assignments[index] = i;
-            let result = self.try_variables(matrix, free_variables, free_variable_max, index + 1, assignments, part1);
-            match result {
-                Some(button_presses) => {
-                    if min_button_presses == 0 || button_presses < min_button_presses {
-                        min_button_presses = button_presses;
+                },
+                Some('{') => {
+                    let start = cursor.pos;
+                    let joltages:Vec<isize> = cursor.list('{', '}')?.iter().map(|&(joltage, _)| joltage).collect();
+                    if joltages.len() != indicator.len() {
+                        return Err(cursor.error(start, &format!("{} joltages for a machine with {} lights", joltages.len(), indicator.len())));
+                    }
+                    cursor.skip_spaces();
+                    if cursor.peek().is_some() {
+                        return Err(cursor.unexpected("the end of the line after the joltages"));
                     }
+                    return Ok(Machine { indicator, buttons, costs, joltages });
                 },
-                None => {}
+                _ => return Err(cursor.unexpected("a button '(' or the joltages '{'"))
             }
         }
-        if min_button_presses == 0 {
-            return None;
-        }
-        return Some(min_button_presses);
-    }
-    /// Swap rows `a`, `b` in the matrix
-    fn swap_rows(matrix:&mut Vec<Vec<Rational>>, a:usize, b:usize) {
-        assert!(a < matrix.len(), "Swap rows: row a {} > matrix rows {} ", a, matrix.len());
-        assert!(b < matrix.len(), "Swap rows: row b {} > matrix rows {} ", b, matrix.len());
-        let first_row_index = min(a,b);
-        let second_row_index = max(a,b);
-        let second_row = matrix.remove(second_row_index);
-        let first_row = matrix.remove(first_row_index);
-        matrix.insert(first_row_index, second_row);
-        matrix.insert(second_row_index, first_row);
-    }
-    /// Scale row so that pivot entry is 1
-    fn scale_row(matrix:&mut Vec<Vec<Rational>>, pivot_index:usize) {
-        if matrix[pivot_index][pivot_index] == 1 || matrix[pivot_index][pivot_index] == 0 {
-            return;
-        }
-        let divisor = matrix[pivot_index][pivot_index];
-        for c in 0..matrix[pivot_index].len() {
-            matrix[pivot_index][c] /= divisor;
-        }
-    }
-    /// Scale row at `row_index` so that denominator in all entries is 1
-    /// and any common gcd has been divided out
-    fn re_scale_row(matrix:&mut Vec<Vec<Rational>>, row_index:usize) {
-        let mut coefficient = 1;
-        for i in 0..matrix[row_index].len() {
-            if matrix[row_index][i] != 0  && coefficient % matrix[row_index][i].denom != 0 {
-                coefficient *= matrix[row_index][i].denom;
+    }
+}
+impl std::fmt::Display for Machine {
+    fn fmt(&self, f: &mut  std::fmt::Formatter) ->  std::fmt::Result {
+        write!(f, "{}", Machine::format_lights(&self.indicator))?;
+        for j in 0..self.buttons.len() {
+            let wiring:Vec<String> = self.buttons[j].iter().map(|light| light.to_string()).collect();
+            write!(f, " ({})", wiring.join(","))?;
+            if self.costs[j] != 1 {
+                write!(f, ":{}", self.costs[j])?;
             }
         }
-        let mut non_zeros = Vec::new();
-        for i in 0..matrix[row_index].len() {
-            matrix[row_index][i] *= coefficient;
-            if matrix[row_index][i] != 0 {
-                non_zeros.push(matrix[row_index][i].num as i128);
-            }
+        return write!(f, " {}", Machine::format_joltages(&self.joltages));
+    }
+}
+
+pub struct Day10 {
+    machines:Vec<Machine>
+}
+impl Day10 {
+    /// A report for each machine of the buttons to press for the lights and
+    /// for the joltages, with the state they produce
+    pub fn explain(&self) -> String {
+        let mut report = String::new();
+        for i in 0..self.machines.len() {
+            report += &format!("Machine on line {}\n", i + 1);
+            report += &self.machines[i].explain();
         }
-        let divisor = util::gcd_list(&non_zeros) as isize;
-        for i in 0..matrix[row_index].len() {
-            matrix[row_index][i] /= divisor;
+        return report;
+    }
+}
+impl Solution for Day10 {
+    fn parse(lines:&Vec<String>) -> Result<Day10, Error> {
+        let mut machines:Vec<Machine> = Vec::new();
+        for i in 0..lines.len() {
+            machines.push(Machine::parse_line(&lines[i], i)?);
         }
+        return Ok(Day10 { machines });
     }
-    /// Perform subtraction on rows following the pivot row such that the entry 
-    /// in the pivot column for each is zero. The pivot row/column entry must be 1
-    fn row_replacement_down(matrix:&mut Vec<Vec<Rational>>, pivot_index:usize, part1:bool) {
-        assert!(matrix[pivot_index][pivot_index] == 1, "Row replacement: pivot entry not 1 ({})", matrix[pivot_index][pivot_index]);
-        for r in pivot_index + 1..matrix.len() {
-            let coefficient = -matrix[r][pivot_index];
-            if coefficient == 0 {
-                continue;
-            }
-            for c in 0..matrix[r].len() {
-                if part1 {
-                    matrix[r][c] =  Rational::new_int(util::abs_mod((matrix[r][c] + coefficient*matrix[pivot_index][c]).num as i128, 2) as isize);
-                }
-                else {
-                    matrix[r][c] = matrix[r][c] + coefficient*matrix[pivot_index][c];
-                }
+    fn part1(&self) -> Result<Answer, Error> {
+        let mut part1:usize = 0;
+        for i in 0..self.machines.len() {
+            match self.machines[i].indicator_presses()? {
+                MinCostSolution::Optimal(presses) => {
+                    let machine = &self.machines[i];
+                    part1 = machine.cost(&machine.press_counts(&presses)).and_then(|cost| part1.checked_add(cost)).ok_or(Machine::overflow(i))?;
+                },
+                MinCostSolution::Inconsistent => return Err(Error::NoSolution(format!("no button presses match the indicator lights of the machine on line {}", i + 1)))
             }
         }
+        return Ok(Answer::from(part1));
     }
-    /// Perform subtraction on rows following the pivot row such that the entry 
-    /// in the pivot column for each is zero. The pivot row/column entry must be 1
-    fn row_replacement_up(matrix:&mut Vec<Vec<Rational>>, pivot_index:usize, part1:bool) {
-        assert!(matrix[pivot_index][pivot_index] == 1, "Row replacement: pivot entry not 1 ({})", matrix[pivot_index][pivot_index]);
-        for r in 0..pivot_index {
-            let coefficient = -matrix[r][pivot_index];
-            if coefficient == 0 {
-                continue;
-            }
-            for c in 0..matrix[r].len() {
-                if part1 {
-                    matrix[r][c] = Rational::new_int(util::abs_mod((matrix[r][c] + coefficient*matrix[pivot_index][c]).num as i128, 2) as isize);
-                }
-                else {
-                    matrix[r][c] = matrix[r][c] + coefficient*matrix[pivot_index][c];
-                }
+    fn part2(&self) -> Result<Answer, Error> {
+        let mut part2:usize = 0;
+        for i in 0..self.machines.len() {
+            match self.machines[i].joltage_presses()? {
+                Some(presses) => part2 = self.machines[i].cost(&presses).and_then(|cost| part2.checked_add(cost)).ok_or(Machine::overflow(i))?,
+                None => return Err(Error::NoSolution(format!("no button presses match the joltages of the machine on line {}", i + 1)))
             }
         }
+        return Ok(Answer::from(part2));
     }
 }
 
-/// Print the solutions to day 10 for the given input `lines`
-pub fn run(lines:&Vec<String>) {
-    let mut part1 = 0;
-    let mut part2 = 0;
-    let mut machines:Vec<Machine> = Vec::new();
-    for i in 0..lines.len() {
-        let parts:Vec<&str> = lines[i].split(" ").collect();
-        let mut indicator:Vec<isize> = Vec::new();
-        let mut buttons:Vec<Vec<isize>> = Vec::new();
-        let mut joltages:Vec<isize> = Vec::new();
-        for part in parts {
-            match part.chars().nth(0).unwrap() {
-                '[' => {
-                    //indicator = &part[1..(part.len() - 1)];
-                    for i in 1..part.len() - 1 {
-                        if part.chars().nth(i).unwrap() == '#' {
-                            indicator.push(1);
-                        }
-                        else {
-                            indicator.push(0);
-                        }
-                    }
-                },
-                '(' => {
-                    buttons.push(util::ints_in_string(&part.to_string()));
-                },
-                '{' => {
-                    joltages = util::ints_in_string(&part.to_string());
-                },
-                _ => {}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util;
+
+    fn example() -> Day10 {
+        let lines = util::read_lines(include_str!("../testdata/day10.txt").as_bytes()).unwrap();
+        return Day10::parse(&lines).unwrap();
+    }
+    #[test]
+    fn part1_example() {
+        assert_eq!(example().part1(), Ok(Answer::Int(7)));
+    }
+    #[test]
+    fn part2_example() {
+        assert_eq!(example().part2(), Ok(Answer::Int(33)));
+    }
+    #[test]
+    fn more_buttons_than_lights() {
+        let lines = vec!["[.##] (0) (1,2) (1,2) (0,1) (0,2) {5,1,1}".to_string()];
+        let day10 = Day10::parse(&lines).unwrap();
+        assert_eq!(day10.part1(), Ok(Answer::Int(1)));
+        assert_eq!(day10.part2(), Ok(Answer::Int(5)));
+    }
+    #[test]
+    fn joltage_press_vectors() {
+        for machine in &example().machines {
+            let presses = machine.joltage_presses().unwrap().unwrap();
+            let mut joltages = vec![0; machine.joltages.len()];
+            for j in 0..presses.len() {
+                for &light in &machine.buttons[j] {
+                    joltages[light as usize] += presses[j] as isize;
+                }
             }
+            assert_eq!(joltages, machine.joltages);
+        }
+    }
+    #[test]
+    fn many_lights() {
+        // 70 lights with a button for each light and the one after it, and
+        // two spare buttons, leaving a single free button, so the null space
+        // is walked in Gray code order. No button toggles more than two
+        // lights, so lighting them all takes 35 presses.
+        let n = 70;
+        let mut line = format!("[{}]", "#".repeat(n));
+        for i in 0..n - 1 {
+            line += &format!(" ({},{})", i, i + 1);
         }
-        let machine = Machine{indicator:indicator, buttons:buttons, joltages:joltages};
-        machines.push(machine);
+        line += &format!(" ({}) (0,{})", n - 1, n - 1);
+        line += &format!(" {{{}}}", vec!["1"; n].join(","));
+        let day10 = Day10::parse(&vec![line]).unwrap();
+        assert_eq!(day10.part1(), Ok(Answer::Int(35)));
+    }
+    #[test]
+    fn explain() {
+        let report = example().explain();
+        assert!(report.starts_with("Machine on line 1\n  Indicator lights: rank 4, free buttons 3, 5\n"));
+        assert_eq!(report.matches(": ok").count(), 6);
+        let day10 = Day10::parse(&vec!["[.#] (0) {1,2}".to_string()]).unwrap();
+        assert_eq!(day10.explain().matches("infeasible").count(), 2);
+    }
+    #[test]
+    fn button_costs() {
+        // Pressing (0,1) once is cheapest unless it costs more than (0) and
+        // (1) together
+        let cheap = Day10::parse(&vec!["[##] (0):2 (1):2 (0,1):3 {4,4}".to_string()]).unwrap();
+        assert_eq!(cheap.part1(), Ok(Answer::Int(3)));
+        assert_eq!(cheap.part2(), Ok(Answer::Int(12)));
+        let dear = Day10::parse(&vec!["[##] (0):2 (1):2 (0,1):5 {4,4}".to_string()]).unwrap();
+        assert_eq!(dear.part1(), Ok(Answer::Int(4)));
+        assert_eq!(dear.part2(), Ok(Answer::Int(16)));
+        assert!(dear.explain().contains("presses per button 4 4 0 (8 in total, costing 16)"));
+    }
+    #[test]
+    fn round_trip() {
+        let lines = util::read_lines(include_str!("../testdata/day10.txt").as_bytes()).unwrap();
+        let day10 = Day10::parse(&lines).unwrap();
+        for i in 0..lines.len() {
+            assert_eq!(day10.machines[i].to_string(), lines[i]);
+        }
+        let costed = "[#.]  (0):3 ()  (0,1):0 {2,1}";
+        let machine = Machine::parse_line(costed, 0).unwrap();
+        assert_eq!(machine.to_string(), "[#.] (0):3 () (0,1):0 {2,1}");
+        assert_eq!(Machine::parse_line(&machine.to_string(), 0), Ok(machine));
+    }
+    #[test]
+    fn parse_errors() {
+        let error = |line:&str| Day10::parse(&vec![line.to_string()]).err().unwrap().to_string();
+        assert_eq!(error("[.#] (0) (2) {1,2}"), "line 1, column 11: button wired to light 2 of a machine with 2 lights");
+        assert_eq!(error("[.#] (0) {1,2,3}"), "line 1, column 10: 3 joltages for a machine with 2 lights");
+        assert_eq!(error("[.#] (0) x {1,2}"), "line 1, column 10: expected a button '(' or the joltages '{', found 'x'");
+        assert_eq!(error("[.x] (0) {1,2}"), "line 1, column 3: expected an indicator light '.' or '#', or ']', found 'x'");
+        assert_eq!(error("[.#](0) {1,2}"), "line 1, column 5: expected a space, found '('");
+        assert_eq!(error("[.#] (0 1) {1,2}"), "line 1, column 8: expected ',' or ')', found ' '");
+        assert_eq!(error("[.#] (1,1) {1,2}"), "line 1, column 9: button wired to light 1 twice");
+        assert_eq!(error("[.#] (0):-2 {1,2}"), "line 1, column 10: expected a number, found '-'");
+        assert_eq!(error("[.#] (0) {1,2} (1)"), "line 1, column 16: expected the end of the line after the joltages, found '('");
+        assert_eq!(error("[.#] (0)"), "line 1, column 9: expected a button '(' or the joltages '{', found the end of the line");
+    }
+    #[test]
+    fn hard_joltages() {
+        // Machines with six free buttons whose searches once took seconds:
+        // the first has duplicate buttons, and the second only reaches the
+        // relaxation's bound deep in a depth first search
+        let lines = vec![
+            "[##########] (0,3,5,6,7) (0,4,7) (1,3,4) (2,3,6) (3,4) (0,2,3,8) (4,7,9) (3,7,9) (3,7,9) (2,7,9) (0,2,4,5,7) (3,5) (0,1,5,7,9) (3,4) (8) (9) {105,27,101,185,137,77,25,259,61,187}".to_string(),
+            "[...##.#.#.] (1,3,4,5) (0,2,4) (2,3,7,8,9) (5) (2,5,6,7,9) (3,7) (4,9) (2,6,9) (1,4) (4,5,6,7) (0,4,5,9) (1,2,3,7,8) (0,1,2,3,8) (0,1,7,9) (3) (0,6,7,9) {234,136,218,151,215,174,197,282,105,322}".to_string()
+        ];
+        let day10 = Day10::parse(&lines).unwrap();
+        assert_eq!(day10.part2(), Ok(Answer::Int(350 + 510)));
+    }
+    #[test]
+    fn large_joltages() {
+        // The joltages sum past i64::MAX, so the program overflows i64 but is
+        // solved over the wider types, pressing (0,1) as often as possible
+        let lines = vec!["[..] (0) (0,1) (1) {4611686018427387907,4611686018427387903}".to_string()];
+        let day10 = Day10::parse(&lines).unwrap();
+        let machine = &day10.machines[0];
+        assert!(matches!(machine.joltage_presses_in::<i64>(), Err(Error::Overflow(_))));
+        assert_eq!(machine.joltage_presses_in::<BigInt>(), Ok(Some(vec![4, 4611686018427387903, 0])));
+        assert_eq!(machine.joltage_presses(), Ok(Some(vec![4, 4611686018427387903, 0])));
+        assert_eq!(day10.part2(), Ok(Answer::Int(4611686018427387907)));
+        // Presses adding up to more than isize::MAX on a light overflow the
+        // joltages the presses are checked against
+        assert_eq!(machine.joltages_after(&[4, 4611686018427387903, 0]), Some(machine.joltages.clone()));
+        assert_eq!(machine.joltages_after(&[isize::MAX as usize, 1, 0]), None);
+        assert_eq!(machine.joltages_after(&[0, usize::MAX, 0]), None);
     }
-    for i in 0..machines.len() {
-        part1 += machines[i].rref_solve(true);
+    #[test]
+    fn many_buttons() {
+        // Buttons with no lights never help however many there are, and 41
+        // buttons on 10 lights leave a null space of over 30 dimensions but a
+        // column space of 10, so the presses are found over the column space
+        let lines = vec![format!("[.]{} {{0}}", " ()".repeat(64))];
+        assert_eq!(Day10::parse(&lines).unwrap().part1(), Ok(Answer::Int(0)));
+        let mut line = "[#########.]".to_string();
+        for i in 0..10 {
+            line += &format!(" ({})", i);
+        }
+        let mut seed:u64 = 1;
+        for _ in 0..31 {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            let lights:Vec<String> = (0..10).filter(|i| seed >> (32 + i) & 1 == 1).map(|i| i.to_string()).collect();
+            line += &format!(" ({})", lights.join(","));
+        }
+        line += &format!(" {{{}}}", ["0"; 10].join(","));
+        let day10 = Day10::parse(&vec![line]).unwrap();
+        assert_eq!(day10.part1(), Ok(Answer::Int(2)));
+    }
+    #[test]
+    fn many_lights_few_free_buttons() {
+        // 70 lights with a button for each and for the first five pairs of
+        // neighbours leave a null space of 5 dimensions, which is walked in
+        // Gray code order. Pairs light the first six in three presses.
+        let mut line = format!("[{}]", "#".repeat(70));
+        for i in 0..70 {
+            line += &format!(" ({})", i);
+        }
+        for i in 0..5 {
+            line += &format!(" ({},{})", i, i + 1);
+        }
+        line += &format!(" {{{}}}", ["0"; 70].join(","));
+        assert_eq!(Day10::parse(&vec![line]).unwrap().part1(), Ok(Answer::Int(3 + 64)));
+    }
+    #[test]
+    fn many_buttons_column_space() {
+        // 65 buttons on 10 lights, one for each light, each pair of lights
+        // and each three lights in a row around the ring, leave a null space
+        // of 55 dimensions, so the presses are found over the column space.
+        // No button lights more than three, so lighting all ten takes four.
+        let mut line = "[##########]".to_string();
+        for i in 0..10 {
+            line += &format!(" ({})", i);
+        }
+        for i in 0..10 {
+            for j in i + 1..10 {
+                line += &format!(" ({},{})", i, j);
+            }
+        }
+        for i in 0..10 {
+            line += &format!(" ({},{},{})", i, (i + 1) % 10, (i + 2) % 10);
+        }
+        line += &format!(" {{{}}}", ["0"; 10].join(","));
+        assert_eq!(Day10::parse(&vec![line]).unwrap().part1(), Ok(Answer::Int(4)));
+    }
+    #[test]
+    fn many_lights_and_buttons() {
+        // 23 lights with a button for each light and for each pair of lights
+        // one or two apart leaves too large a search either way, so the
+        // presses are found by branch and bound. No button toggles more than
+        // two lights, so lighting all 23 takes 12 presses.
+        let n = 23;
+        let machine = |indicator:char| {
+            let mut line = format!("[{}]", indicator.to_string().repeat(n));
+            for i in 0..n {
+                line += &format!(" ({})", i);
+            }
+            for gap in 1..=2 {
+                for i in 0..n - gap {
+                    line += &format!(" ({},{})", i, i + gap);
+                }
+            }
+            line += &format!(" {{{}}}", ["0"; 23].join(","));
+            return line;
+        };
+        let day10 = Day10::parse(&vec![machine('.')]).unwrap();
+        assert_eq!(day10.part1(), Ok(Answer::Int(0)));
+        assert!(day10.explain().contains("press buttons none (0 in total)"));
+        assert_eq!(Day10::parse(&vec![machine('#')]).unwrap().part1(), Ok(Answer::Int(12)));
     }
-    println!("Part 1: {}", part1);
-    for i in 0..machines.len() {
-        part2 += machines[i].rref_solve(false);
+    #[test]
+    fn cost_overflow() {
+        let costly = |cost:usize| Day10::parse(&vec![format!("[#.] (0):{} (1) {{3,0}}", cost)]);
+        assert!(costly(MAX_COST).is_ok());
+        assert_eq!(costly(MAX_COST + 1).err().unwrap().to_string(), format!("line 1, column 10: button cost {} is more than {}", MAX_COST + 1, MAX_COST));
+        assert!(costly(9000000000000000000).is_err());
+        // Each machine's cost fits but the total doesn't
+        let line = format!("[#] (0):{} {{{}}}", MAX_COST, isize::MAX / MAX_COST as isize);
+        let day10 = Day10::parse(&vec![line.clone(), line.clone(), line]).unwrap();
+        assert!(matches!(day10.part2(), Err(Error::Overflow(_))));
     }
-    println!("Part 2: {}", part2);
 }
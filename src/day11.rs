@@ -1,72 +1,169 @@
-use std::collections::{HashMap};
+use crate::util::{BigInt, DiGraph};
+use std::collections::HashMap;
+use crate::error::Error;
+use crate::solution::{self, Answer, Params, Solution};
 
-type Addr = [char;3];
-
-/// Count paths to `dst` from `curr` to `dst` recursively using memoization
-pub fn path_count(outputs:&HashMap<Addr, Vec<Addr>>, cache:&mut HashMap<Addr, usize>, curr:Addr, dst:Addr) -> usize {
-    if cache.contains_key(&curr) {
-        return *cache.get(&curr).unwrap();
+pub struct Day11 {
+    /// Devices with an edge to each of their output devices
+    graph:DiGraph,
+    /// Count paths with arbitrary precision (param `bigint`)
+    bigint:bool
+}
+impl Day11 {
+    /// Count the paths from device `from` to device `to` that pass through
+    /// all of the `waypoints` devices. There are none if any of the devices 
+    /// doesn't exist.
+    fn count_paths(&self, from:&str, to:&str, waypoints:&[&str]) -> Result<Answer, Error> {
+        let (from_id, to_id) = match (self.graph.id(from), self.graph.id(to)) {
+            (Some(f), Some(t)) => (f, t),
+            _ => return Ok(Answer::Int(0))
+        };
+        let mut waypoint_ids = Vec::new();
+        for w in waypoints {
+            match self.graph.id(w) {
+                Some(id) => waypoint_ids.push(id),
+                None => return Ok(Answer::Int(0))
+            }
+        }
+        if self.bigint {
+            return Ok(Answer::from(self.graph.count_paths_via::<BigInt>(from_id, to_id, &waypoint_ids)?));
+        }
+        return Ok(Answer::from(self.graph.count_paths_via::<i64>(from_id, to_id, &waypoint_ids).map_err(solution::suggest_bigint)?));
     }
-    if curr == dst {
-        return 1;
+    /// Fill colour for the devices highlighted in `to_dot`
+    fn highlight(name:&str) -> Option<&'static str> {
+        match name {
+            "you" | "svr" => return Some("palegreen"),
+            "dac" | "fft" => return Some("gold"),
+            "out" => return Some("lightcoral"),
+            _ => return None
+        }
     }
-    if !outputs.contains_key(&curr) {
-        return 0;
+    /// Format the device graph in Graphviz DOT format, highlighting the start
+    /// (`you`, `svr`), waypoint (`dac`, `fft`) and end (`out`) devices. If
+    /// `counts` is set, each device is labelled with the number of paths to
+    /// it from `you` and from `svr`.
+    pub fn to_dot(&self, counts:bool) -> Result<String, Error> {
+        let mut path_counts:Vec<(&str, HashMap<usize, BigInt>)> = Vec::new();
+        if counts {
+            for start in ["you", "svr"] {
+                if let Some(id) = self.graph.id(start) {
+                    path_counts.push((start, self.graph.count_paths_from(id)?));
+                }
+            }
+        }
+        return Ok(self.graph.to_dot(|id| {
+            let name = self.graph.name(id);
+            let mut attributes = Vec::new();
+            if let Some(colour) = Day11::highlight(name) {
+                attributes.push(("style".to_string(), "filled".to_string()));
+                attributes.push(("fillcolor".to_string(), colour.to_string()));
+            }
+            if !path_counts.is_empty() {
+                let mut label = name.to_string();
+                for (start, paths) in &path_counts {
+                    label.push_str(&format!("\n{}: {}", start, paths.get(&id).cloned().unwrap_or_default()));
+                }
+                attributes.push(("label".to_string(), label));
+            }
+            return attributes;
+        }));
+    }
+}
+impl Solution for Day11 {
+    fn parse(lines:&Vec<String>) -> Result<Day11, Error> {
+        return Day11::parse_with(lines, &Params::new());
     }
-    let mut paths = 0;
-    for next in outputs.get(&curr).unwrap() {
-        paths += path_count(outputs, cache, *next, dst);
+    fn parse_with(lines:&Vec<String>, params:&Params) -> Result<Day11, Error> {
+        let bigint = params.get("bigint", false)?;
+        let mut graph = DiGraph::new();
+        for i in 0..lines.len() {
+            let (src_str, outs_str) = match lines[i].split_once(": ") {
+                Some(parts) => parts,
+                None => return Err(Error::parse_at(i, 0, "expected a device name followed by ': '"))
+            };
+            if src_str.is_empty() {
+                return Err(Error::parse_at(i, 0, "missing device name"));
+            }
+            let src = graph.node(src_str);
+            let mut column = src_str.chars().count() + 2;
+            for out in outs_str.split(" ") {
+                if out.is_empty() {
+                    return Err(Error::parse_at(i, column, "missing output device name"));
+                }
+                let dst = graph.node(out);
+                graph.add_edge(src, dst);
+                column += out.chars().count() + 1;
+            }
+        }
+        return Ok(Day11 { graph, bigint });
+    }
+    /// Find all paths from you to out
+    fn part1(&self) -> Result<Answer, Error> {
+        return self.count_paths("you", "out", &[]);
+    }
+    /// Find all paths from svr to out that pass through both dac and fft
+    fn part2(&self) -> Result<Answer, Error> {
+        return self.count_paths("svr", "out", &["dac", "fft"]);
     }
-    cache.insert(curr, paths);
-    return paths;
 }
 
-// try to convert a 3 character &str to a [char;3]
-fn to_addr(string:&str) -> Addr {
-    let result:Addr = string.chars().collect::<Vec<char>>().try_into().unwrap();
-    return result;
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util;
 
-/// Print the solutions to day 11 for the given input `lines`
-pub fn run(lines:&Vec<String>) {
-    let part1;
-    let part2;
-    let mut outputs:HashMap<Addr, Vec<Addr>> = HashMap::new();
-    // I had some difficulty with lifetimes using &str keys in the cache 
-    // HashMap, thus the constant size Addr type.
-    for i in 0..lines.len() {
-        let parts = lines[i].split(": ").collect::<Vec<&str>>();
-        let src:Addr = to_addr(parts[0]);
-        let outs = parts[1].split(" ").map(|x| to_addr(x)).collect::<Vec<Addr>>();
-        outputs.insert(src, outs);
+    fn example(input:&str) -> Day11 {
+        let lines = util::read_lines(input.as_bytes()).unwrap();
+        return Day11::parse(&lines).unwrap();
+    }
+    #[test]
+    fn part1_example() {
+        assert_eq!(example(include_str!("../testdata/day11.txt")).part1(), Ok(Answer::Int(5)));
+    }
+    #[test]
+    fn part2_example() {
+        assert_eq!(example(include_str!("../testdata/day11_part2.txt")).part2(), Ok(Answer::Int(2)));
+    }
+    #[test]
+    fn missing_device_name() {
+        let lines = vec!["aaa: you  hhh".to_string()];
+        assert!(matches!(Day11::parse(&lines), Err(Error::Parse { line:1, column:10, .. })));
+        // Columns count characters, not bytes
+        let lines = vec!["café: naïve  out".to_string()];
+        assert!(matches!(Day11::parse(&lines), Err(Error::Parse { line:1, column:13, .. })));
+    }
+    #[test]
+    fn long_names_and_dot() {
+        let lines = vec!["you: router-1 x".to_string(), "router-1: out".to_string(), "x: router-1".to_string()];
+        let day11 = Day11::parse(&lines).unwrap();
+        assert_eq!(day11.part1(), Ok(Answer::Int(2)));
+        let dot = day11.to_dot(true).unwrap();
+        assert!(dot.contains("    \"you\" [style=\"filled\", fillcolor=\"palegreen\", label=\"you\\nyou: 1\"];\n"));
+        assert!(dot.contains("    \"out\" [style=\"filled\", fillcolor=\"lightcoral\", label=\"out\\nyou: 2\"];\n"));
+        assert!(dot.contains("    \"router-1\" -> \"out\";\n"));
+        assert!(day11.to_dot(false).unwrap().contains("    \"router-1\";\n"));
+    }
+    #[test]
+    fn cycle() {
+        let lines = vec!["you: aaa".to_string(), "aaa: bbb out".to_string(), "bbb: you".to_string()];
+        let day11 = Day11::parse(&lines).unwrap();
+        assert_eq!(day11.part1(), Err(Error::Input("graph has a cycle: you -> aaa -> bbb -> you".to_string())));
+    }
+    #[test]
+    fn bigint() {
+        // A chain of 130 diamonds from you to out has 2^130 paths
+        let mut lines = Vec::new();
+        for i in 0..130 {
+            let node = if i == 0 { "you".to_string() } else { format!("n{}", i) };
+            let next = if i == 129 { "out".to_string() } else { format!("n{}", i + 1) };
+            lines.push(format!("{}: l{} r{}", node, i, i));
+            lines.push(format!("l{}: {}", i, next));
+            lines.push(format!("r{}: {}", i, next));
+        }
+        assert!(matches!(Day11::parse(&lines).unwrap().part1(), Err(Error::Overflow(_))));
+        let mut params = Params::new();
+        params.insert("bigint", "true");
+        assert_eq!(Day11::parse_with(&lines, &params).unwrap().part1(), Ok(Answer::Big(BigInt::from(2).pow(130))));
     }
-    let you = to_addr("you");
-    let out = to_addr("out");
-    let svr = to_addr("svr");
-    let dac = to_addr("dac");
-    let fft = to_addr("fft");
-    let mut cache:HashMap<Addr, usize> = HashMap::new();
-    // Part 1 - find all paths from you to out
-    part1 = path_count(&outputs, &mut cache, you, out);
-    cache.clear();
-
-    // Part 2 - the paths from svr to out that pass through both dac and fft
-    // are the sum of the products of paths that path between the nodes in each
-    // order.
-    let svr_dac = path_count(&outputs, &mut cache, svr, dac);
-    cache.clear();
-    let dac_fft = path_count(&outputs, &mut cache, dac, fft);
-    cache.clear();
-    let fft_out = path_count(&outputs, &mut cache, fft, out);
-    cache.clear();
-    let svr_fft = path_count(&outputs, &mut cache, svr, fft);
-    cache.clear();
-    let fft_dac = path_count(&outputs, &mut cache, fft, dac);
-    cache.clear();
-    let dac_out = path_count(&outputs, &mut cache, dac, out);
-    cache.clear();
-    part2 = (svr_dac * dac_fft * fft_out) + (svr_fft * fft_dac * dac_out);
-    
-    println!("Part 1: {}", part1);
-    println!("Part 2: {}", part2);
 }
@@ -1,9 +1,13 @@
 use crate::util;
 use util::Vec2;
 use std::{collections::HashSet};
+use crate::error::Error;
+use crate::solution::{Answer, Solution};
 
 struct Shape {
-    pub transforms:Vec<HashSet<Vec2>>,
+    /// Each distinct rotation and reflection of the shape, with its cells
+    /// moved to start at the origin and sorted in reading order
+    pub transforms:Vec<Vec<Vec2>>,
     // pub id:usize
 }
 impl Shape {
@@ -19,21 +23,27 @@ impl Shape {
                 }
             }
         }
-        let mut transforms = Vec::new();
-        for rotate in 0..4 {
-            let mut cells = base_cells.clone();
-            for reflect in 0..1 {
-                
-                for _ in 0..rotate {
-                    cells = rotate_clockwise(&cells);
-                }
-                for _ in 0..reflect {
-                    cells = reflect_x(&cells);
+        let mut transforms:Vec<Vec<Vec2>> = Vec::new();
+        let mut cells = base_cells;
+        for _ in 0..4 {
+            for reflected in [cells.clone(), reflect_x(&cells)] {
+                let normalized = normalize(&reflected);
+                if !transforms.contains(&normalized) {
+                    transforms.push(normalized);
                 }
             }
-            transforms.push(cells);
+            cells = rotate_clockwise(&cells);
         }
-        return Shape{transforms:transforms};
+        return Shape{transforms};
+    }
+    /// Number of cells in the shape
+    pub fn size(&self) -> usize {
+        return self.transforms[0].len();
+    }
+    /// Width and height of the smallest box the shape fits in
+    pub fn bounds(&self) -> (isize, isize) {
+        let cells = &self.transforms[0];
+        return (cells.iter().map(|c| c.x).max().unwrap_or(-1) + 1, cells.iter().map(|c| c.y).max().unwrap_or(-1) + 1);
     }
 }
 
@@ -63,32 +73,162 @@ pub fn rotate_clockwise(cells:&HashSet<Vec2>) -> HashSet<Vec2> {
     return rotated;
 }
 
-/// Print the solutions to day 12 for the given input `lines`
-pub fn run(lines:&Vec<String>) {
-    let mut part1 = 0;
-    let sections = util::sections(lines);
-    let mut shapes = Vec::new();
-    for i in 0..sections.len() - 1 {
-        shapes.push(Shape::new(&sections[i]));
+/// Move `cells` so their smallest coordinates are zero and sort them in
+/// reading order
+fn normalize(cells:&HashSet<Vec2>) -> Vec<Vec2> {
+    let min_x = cells.iter().map(|c| c.x).min().unwrap_or(0);
+    let min_y = cells.iter().map(|c| c.y).min().unwrap_or(0);
+    let mut normalized:Vec<Vec2> = cells.iter().map(|c| Vec2::new(c.x - min_x, c.y - min_y)).collect();
+    normalized.sort_by_key(|c| (c.y, c.x));
+    return normalized;
+}
+
+pub struct Day12 {
+    shapes:Vec<Shape>,
+    regions:Vec<Region>
+}
+impl Day12 {
+    /// Test if all of a region's presents fit in it.
+    /// I did spend over an hour pursuing other approaches before thinking 
+    /// to try this: a region with a smaller area than the sum of the cells 
+    /// of all the required present shapes can't fit the presents, and one
+    /// with room for every present in its own box can. That decides every
+    /// region of the real inputs. Any other region, such as the third region
+    /// of the example, is searched by packing the presents one at a time.
+    fn fits(&self, region:&Region) -> bool {
+        let shapes = &self.shapes;
+        let mut total_shape_size = 0;
+        let mut total_presents = 0;
+        for j in 0..shapes.len() {
+            total_shape_size += region.shape_counts[j] as usize * shapes[j].size();
+            total_presents += region.shape_counts[j] as usize;
+        }
+        let region_area = (region.x * region.y) as usize;
+        if total_shape_size > region_area {
+            return false;
+        }
+        let box_width = shapes.iter().map(|s| s.bounds().0).max().unwrap_or(1).max(1);
+        let box_height = shapes.iter().map(|s| s.bounds().1).max().unwrap_or(1).max(1);
+        if ((region.x / box_width) * (region.y / box_height)) as usize >= total_presents {
+            return true;
+        }
+        let mut filled = vec![false; region_area];
+        let mut counts = region.shape_counts.clone();
+        // Every reflection of each shape is tried, so the region can be
+        // turned on its side, and packing along its shorter side keeps fewer
+        // cells undecided at once
+        return self.pack(region.x.min(region.y), &mut filled, 0, &mut counts, total_presents, region_area - total_shape_size);
     }
-    let numbers = util::ints_in_strings(sections.last().unwrap());
-    let mut regions = Vec::new();
-    for i in 0..numbers.len() {
-        regions.push(Region{x:numbers[i][0], y:numbers[i][1], shape_counts:vec![numbers[i][2],numbers[i][3],numbers[i][4],numbers[i][5],numbers[i][6],numbers[i][7]]});
+    /// Place the `remaining` presents still in `counts` in the unfilled cells
+    /// of a region `width` cells wide, given that every cell before `start`
+    /// is decided. The first undecided cell is either covered by a present
+    /// whose first cell in reading order it is, or left empty if fewer than
+    /// `gaps` cells have been left empty so far.
+    fn pack(&self, width:isize, filled:&mut Vec<bool>, start:usize, counts:&mut Vec<isize>, remaining:usize, gaps:usize) -> bool {
+        if remaining == 0 {
+            return true;
+        }
+        let Some(cell) = (start..filled.len()).find(|&i| !filled[i]) else {
+            return false;
+        };
+        let height = filled.len() as isize / width;
+        let corner = Vec2::new(cell as isize % width, cell as isize / width);
+        for j in 0..self.shapes.len() {
+            if counts[j] == 0 {
+                continue;
+            }
+            for transform in &self.shapes[j].transforms {
+                let offset = Vec2::new(corner.x - transform[0].x, corner.y - transform[0].y);
+                let cells:Vec<Vec2> = transform.iter().map(|&c| c + offset).collect();
+                if !cells.iter().all(|c| c.x >= 0 && c.x < width && c.y >= 0 && c.y < height && !filled[(c.y * width + c.x) as usize]) {
+                    continue;
+                }
+                for c in &cells {
+                    filled[(c.y * width + c.x) as usize] = true;
+                }
+                counts[j] -= 1;
+                let packed = self.pack(width, filled, cell + 1, counts, remaining - 1, gaps);
+                counts[j] += 1;
+                for c in &cells {
+                    filled[(c.y * width + c.x) as usize] = false;
+                }
+                if packed {
+                    return true;
+                }
+            }
+        }
+        if gaps > 0 {
+            filled[cell] = true;
+            let packed = self.pack(width, filled, cell + 1, counts, remaining, gaps - 1);
+            filled[cell] = false;
+            return packed;
+        }
+        return false;
     }
-    // I did spend over an hour pursuing other approaches before thinking to 
-    // try this
-    // Only the regions that have a smaller area than the sum of the cells of
-    // all the required present shapes are unable to fit all the presents.
-    for i in 0..regions.len() {
-        let region_area = (regions[i].x * regions[i].y) as usize;
-        let mut total_shape_size = 0;
-        for j in 0..6 {
-            total_shape_size += regions[i].shape_counts[j] as usize * shapes[j].transforms[0].len();
+}
+impl Solution for Day12 {
+    fn parse(lines:&Vec<String>) -> Result<Day12, Error> {
+        let sections = util::sections_indexed(lines);
+        if sections.len() < 2 {
+            return Err(Error::Input("expected shape sections followed by a section of regions".to_string()));
+        }
+        let mut shapes = Vec::new();
+        for i in 0..sections.len() - 1 {
+            let (start, section) = &sections[i];
+            if section.is_empty() || !section[0].ends_with(":") {
+                return Err(Error::parse_at(*start, 0, "expected a shape header such as '0:'"));
+            }
+            shapes.push(Shape::new(section));
         }
-        if total_shape_size <= region_area {
-            part1 += 1;
+        let (start, region_lines) = sections.last().unwrap();
+        let numbers = util::ints_in_strings(region_lines);
+        let mut regions = Vec::new();
+        for i in 0..numbers.len() {
+            if numbers[i].len() != 2 + shapes.len() || numbers[i].iter().any(|&n| n < 0) {
+                return Err(Error::parse_at(start + i, 0, &format!("expected a region size and {} non-negative shape counts", shapes.len())));
+            }
+            regions.push(Region{x:numbers[i][0], y:numbers[i][1], shape_counts:numbers[i][2..].to_vec()});
+        }
+        return Ok(Day12 { shapes, regions });
+    }
+    fn part1(&self) -> Result<Answer, Error> {
+        let mut part1:usize = 0;
+        for region in &self.regions {
+            if self.fits(region) {
+                part1 += 1;
+            }
         }
+        return Ok(Answer::from(part1));
+    }
+    /// There is no part 2 puzzle on day 12
+    fn part2(&self) -> Result<Answer, Error> {
+        return Ok(Answer::None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Day12 {
+        let lines = util::read_lines(include_str!("../testdata/day12.txt").as_bytes()).unwrap();
+        return Day12::parse(&lines).unwrap();
+    }
+    #[test]
+    fn part1_example() {
+        assert_eq!(example().part1(), Ok(Answer::Int(2)));
+    }
+    #[test]
+    fn shape_transforms() {
+        // The fifth shape is symmetric about its middle row, so reflecting
+        // it gives the same shapes as rotating it
+        let day12 = example();
+        assert_eq!(day12.shapes[4].transforms.len(), 4);
+        assert_eq!(day12.shapes[0].transforms.len(), 8);
+        assert_eq!((day12.shapes[0].size(), day12.shapes[0].bounds()), (7, (3, 3)));
+    }
+    #[test]
+    fn part2_example() {
+        assert_eq!(example().part2(), Ok(Answer::None));
     }
-    println!("Part 1: {}", part1);
 }
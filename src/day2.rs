@@ -1,13 +1,15 @@
 use crate::util;
+use crate::error::{self, Error};
+use crate::solution::{Answer, Solution};
 
 /// Validate the given `id` against the part 1 or part 2 rules
 fn valid(id:usize, part1:bool) -> bool {
     let digits = util::base_n_digits(id as i128, 10, None);
-    if part1 && digits.len() % 2 != 0 {
+    if part1 && !digits.len().is_multiple_of(2) {
         return true;
     }
     for window in (1..=digits.len() / 2).rev() {
-        if digits.len() % window == 0 {
+        if digits.len().is_multiple_of(window) {
             let repeats = digits.len() / window;
             let mut has_repeat = true;
             for i in 0..window {
@@ -24,24 +26,67 @@ fn valid(id:usize, part1:bool) -> bool {
     }
     return true;
 }
-/// Print the solutions to day 2 for the given input `lines`
-pub fn run(lines:&Vec<String>) {
-    let mut part1 = 0;
-    let mut part2 = 0;
-    let ranges:Vec<&str> = lines[0].split(",").collect();
-    for i in 0..ranges.len() {
-        let parts:Vec<&str> = ranges[i].split("-").collect();
-        let start = parts[0].parse::<usize>().unwrap();
-        let stop = parts[1].parse::<usize>().unwrap();
-        for i in start..=stop {
-            if !valid(i, true) {
-                part1 += i;
+pub struct Day2 {
+    /// Inclusive ranges of product ids
+    ranges:Vec<(usize, usize)>
+}
+impl Day2 {
+    /// Sum the ids in all ranges that are invalid under the part 1 or part 2 
+    /// rules
+    fn invalid_sum(&self, part1:bool) -> usize {
+        let mut sum = 0;
+        for &(start, stop) in self.ranges.iter() {
+            for i in start..=stop {
+                if !valid(i, part1) {
+                    sum += i;
+                }
             }
-            if !valid(i, false) {
-                part2 += i;
+        }
+        return sum;
+    }
+}
+impl Solution for Day2 {
+    fn parse(lines:&Vec<String>) -> Result<Day2, Error> {
+        let mut ranges = Vec::new();
+        if lines.is_empty() {
+            return Err(Error::Input("no ranges found".to_string()));
+        }
+        // Track the column of each range in the line for error reporting
+        let mut column = 0;
+        for range_str in lines[0].split(",") {
+            let parts:Vec<&str> = range_str.split("-").collect();
+            if parts.len() != 2 {
+                return Err(Error::parse_at(0, column, &format!("expected a range start-stop, found '{}'", range_str)));
             }
+            let start = error::parse_field::<usize>(parts[0].trim(), 0, column)?;
+            let stop = error::parse_field::<usize>(parts[1].trim(), 0, column + parts[0].len() + 1)?;
+            ranges.push((start, stop));
+            column += range_str.len() + 1;
         }
+        return Ok(Day2 { ranges });
+    }
+    fn part1(&self) -> Result<Answer, Error> {
+        return Ok(Answer::from(self.invalid_sum(true)));
+    }
+    fn part2(&self) -> Result<Answer, Error> {
+        return Ok(Answer::from(self.invalid_sum(false)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Day2 {
+        let lines = util::read_lines(include_str!("../testdata/day2.txt").as_bytes()).unwrap();
+        return Day2::parse(&lines).unwrap();
+    }
+    #[test]
+    fn part1_example() {
+        assert_eq!(example().part1(), Ok(Answer::Int(1227775554)));
+    }
+    #[test]
+    fn part2_example() {
+        assert_eq!(example().part2(), Ok(Answer::Int(4174379265)));
     }
-    println!("Part 1: {}", part1);
-    println!("Part 2: {}", part2);
 }
@@ -1,3 +1,5 @@
+use crate::error::Error;
+use crate::solution::{Answer, Solution};
 
 /// Recurse over the digit selections for the given number of digits. At each 
 /// index position, find the largest possible leftmost number then recurse to
@@ -40,18 +42,67 @@ pub fn battery_recurse(digits:&Vec<usize>, max_digits: usize, indices:&mut Vec<u
     return Err("Not found".to_string());
 }
 
-/// Print the solutions to day 3 for the given input `lines`
-pub fn run(lines:&Vec<String>) {
-    let mut part1 = 0;
-    let mut part2 = 0;
-    // Storage for the selected indices during recursion
-    let mut indices1:Vec<usize> = vec![0;2];
-    let mut indices2 = vec![0;12];
-    for i in 0..lines.len() {
-        let digits:Vec<usize> = lines[i].chars().map(|x| (x.to_string().parse::<usize>().unwrap())).collect();
-        part1 += battery_recurse(&digits, 2, &mut indices1, 0).unwrap();
-        part2 += battery_recurse(&digits, 12, &mut indices2, 0).unwrap();
-    }
-    println!("Part 1: {}", part1);
-    println!("Part 2: {}", part2);
+pub struct Day3 {
+    /// Battery joltage digits for each bank
+    banks:Vec<Vec<usize>>
+}
+impl Day3 {
+    /// Sum the largest joltages that can be made by selecting `max_digits` 
+    /// batteries from each bank
+    fn total_joltage(&self, max_digits:usize) -> Result<usize, Error> {
+        let mut total = 0;
+        // Storage for the selected indices during recursion
+        let mut indices = vec![0;max_digits];
+        for i in 0..self.banks.len() {
+            if self.banks[i].len() < max_digits {
+                return Err(Error::NoSolution(format!("bank on line {} has fewer than {} batteries", i + 1, max_digits)));
+            }
+            match battery_recurse(&self.banks[i], max_digits, &mut indices, 0) {
+                Ok(joltage) => total += joltage,
+                Err(e) => return Err(Error::NoSolution(format!("bank on line {}: {}", i + 1, e)))
+            }
+        }
+        return Ok(total);
+    }
+}
+impl Solution for Day3 {
+    fn parse(lines:&Vec<String>) -> Result<Day3, Error> {
+        let mut banks = Vec::new();
+        for i in 0..lines.len() {
+            let mut digits:Vec<usize> = Vec::new();
+            for (j, c) in lines[i].chars().enumerate() {
+                match c.to_digit(10) {
+                    Some(d) => digits.push(d as usize),
+                    None => return Err(Error::parse_at(i, j, &format!("expected a battery joltage digit, found '{}'", c)))
+                }
+            }
+            banks.push(digits);
+        }
+        return Ok(Day3 { banks });
+    }
+    fn part1(&self) -> Result<Answer, Error> {
+        return Ok(Answer::from(self.total_joltage(2)?));
+    }
+    fn part2(&self) -> Result<Answer, Error> {
+        return Ok(Answer::from(self.total_joltage(12)?));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util;
+
+    fn example() -> Day3 {
+        let lines = util::read_lines(include_str!("../testdata/day3.txt").as_bytes()).unwrap();
+        return Day3::parse(&lines).unwrap();
+    }
+    #[test]
+    fn part1_example() {
+        assert_eq!(example().part1(), Ok(Answer::Int(357)));
+    }
+    #[test]
+    fn part2_example() {
+        assert_eq!(example().part2(), Ok(Answer::Int(3121910778619)));
+    }
 }
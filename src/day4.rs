@@ -1,41 +1,77 @@
-use std::collections::HashSet;
-use crate::util;
+use crate::util::{Grid, Vec2};
+use crate::error::Error;
+use crate::solution::{Answer, Solution};
 
-/// Print the solutions to day 4 for the given input `lines`
-pub fn run(lines:&Vec<String>) {
-    let mut part1 = 0;
-    let mut part2 = 0;
-    // Load the grid as a DefaultHashMap
-    let (grid, width, height) = util::read_grid_map(lines, '.').unwrap();
-    // Get all initial roll positions in the grid
-    let mut remaining_rolls:HashSet<&util::Vec2> = HashSet::from_iter(grid.keys().into_iter());
-    // In a loop, iterate over rolls to find all rolls that can currently be 
-    // removed. If no rolls are marked as removable, exit the loop. Otherwise,
-    // remove the marked rolls and continue with the next pass, recording the 
-    // number of removed items for the part 1 and part 2 answers.
-    loop {
-        let mut removed_in_loop = HashSet::new();
-        for pos in remaining_rolls.iter() {
-            let mut neighbor_count = 0;
-            for dir in util::adjacent8() {
-                let neighbor = **pos + dir;
-                // Count the neighbor if it's a roll and it hasn't been 
-                // previously removed
-                if neighbor.in_bounds(width, height) 
-                    && remaining_rolls.contains(&neighbor) {
-                    neighbor_count += 1;
-                }
-            }
+/// Grid cell holding a roll of paper
+const ROLL:char = '@';
+/// Empty grid cell
+const EMPTY:char = '.';
+
+pub struct Day4 {
+    /// Initial grid of rolls
+    grid:Grid<char>
+}
+impl Day4 {
+    /// Find all rolls in `grid` that can currently be removed
+    fn removable(grid:&Grid<char>) -> Vec<Vec2> {
+        let mut removable = Vec::new();
+        for pos in grid.find_all(&ROLL) {
+            // Count the neighbors that are rolls which haven't been 
+            // previously removed
+            let neighbor_count = grid.neighbors8(pos).filter(|n| grid[*n] == ROLL).count();
             if neighbor_count < 4 {
-                removed_in_loop.insert((*pos).clone());
+                removable.push(pos);
+            }
+        }
+        return removable;
+    }
+}
+impl Solution for Day4 {
+    fn parse(lines:&Vec<String>) -> Result<Day4, Error> {
+        let grid = Grid::from_lines(lines)?;
+        if let Some(pos) = grid.position(|&c| c != ROLL && c != EMPTY) {
+            return Err(Error::parse_at(pos.y as usize, pos.x as usize, &format!("expected {} or {}, found '{}'", ROLL, EMPTY, grid[pos])));
+        }
+        return Ok(Day4 { grid });
+    }
+    fn part1(&self) -> Result<Answer, Error> {
+        return Ok(Answer::from(Day4::removable(&self.grid).len()));
+    }
+    fn part2(&self) -> Result<Answer, Error> {
+        let mut part2 = 0;
+        let mut remaining = self.grid.clone();
+        // In a loop, find all rolls that can currently be removed. If no rolls
+        // are marked as removable, exit the loop. Otherwise, remove the marked
+        // rolls and continue with the next pass, recording the number of 
+        // removed items.
+        loop {
+            let removed_in_loop = Day4::removable(&remaining);
+            part2 += removed_in_loop.len();
+            // remove marked rolls from remaining rolls
+            for pos in &removed_in_loop {
+                remaining[*pos] = EMPTY;
             }
+            if removed_in_loop.is_empty() { break; }
         }
-        if part1 == 0 { part1 = removed_in_loop.len(); }
-        part2 += removed_in_loop.len();
-        // remove marked rolls from remaining rolls
-        remaining_rolls.retain(|&p| !removed_in_loop.contains(p));
-        if removed_in_loop.len() == 0 { break; }
+        return Ok(Answer::from(part2));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util;
+
+    fn example() -> Day4 {
+        let lines = util::read_lines(include_str!("../testdata/day4.txt").as_bytes()).unwrap();
+        return Day4::parse(&lines).unwrap();
+    }
+    #[test]
+    fn part1_example() {
+        assert_eq!(example().part1(), Ok(Answer::Int(13)));
+    }
+    #[test]
+    fn part2_example() {
+        assert_eq!(example().part2(), Ok(Answer::Int(43)));
     }
-    println!("Part 1: {}", part1);
-    println!("Part 2: {}", part2);
 }
@@ -1,79 +1,137 @@
-use crate::util;
+use crate::error::{self, Error};
+use crate::solution::{Answer, Solution};
 
-/// Print the solutions to day 5 for the given input `lines`
-pub fn run(lines:&Vec<String>) {
-    let mut part1 = 0;
-    let mut part2 = 0;
-    let sections = util::sections(lines);
-    let mut fresh_ids = Vec::new();
-    for i in 0..sections[0].len() {
-        let parts = sections[0][i].split("-").collect::<Vec<&str>>();
-        let start = parts[0].parse::<usize>().unwrap();
-        let stop = parts[1].parse::<usize>().unwrap();
-        fresh_ids.push((start, stop));
-    }
-    // Part 1 - Count fresh ingredients
-    for i in 0..sections[1].len() {
-        let ingredient = sections[1][i].parse::<usize>().unwrap();
-        for j in 0..fresh_ids.len() {
-            if ingredient >= fresh_ids[j].0 && ingredient <= fresh_ids[j].1 {
-                part1 += 1;
-                break;
+pub struct Day5 {
+    /// Inclusive ranges of fresh ingredient ids
+    fresh_ids:Vec<(usize, usize)>,
+    /// Available ingredient ids
+    ingredients:Vec<usize>
+}
+impl Solution for Day5 {
+    fn parse(lines:&Vec<String>) -> Result<Day5, Error> {
+        let mut fresh_ids = Vec::new();
+        let mut ingredients = Vec::new();
+        // Fresh id ranges are listed before the first empty line, and 
+        // ingredient ids after it.
+        let mut in_ranges = true;
+        for i in 0..lines.len() {
+            if lines[i].is_empty() {
+                in_ranges = false;
+                continue;
+            }
+            if in_ranges {
+                let parts = lines[i].split("-").collect::<Vec<&str>>();
+                if parts.len() != 2 {
+                    return Err(Error::parse_at(i, 0, &format!("expected a range start-stop, found '{}'", lines[i])));
+                }
+                let start = error::parse_field::<usize>(parts[0], i, 0)?;
+                let stop = error::parse_field::<usize>(parts[1], i, parts[0].len() + 1)?;
+                if stop < start {
+                    return Err(Error::parse_at(i, 0, &format!("range {} ends before it starts", lines[i])));
+                }
+                fresh_ids.push((start, stop));
+            }
+            else {
+                ingredients.push(error::parse_field::<usize>(&lines[i], i, 0)?);
             }
         }
+        if in_ranges {
+            return Err(Error::Input("no empty line separating fresh id ranges from ingredient ids".to_string()));
+        }
+        return Ok(Day5 { fresh_ids, ingredients });
     }
-    // Part 2 - Merge fresh id ranges until no more merges are possible, then 
-    // sum the range widths
-    loop {
-        let mut next_fresh_ids:Vec<(usize, usize)> = Vec::new();
-        let mut merges = 0;
-        for i in 0..fresh_ids.len() {
-            let mut merged = false;
-            for j in 0..next_fresh_ids.len() {
-                // If the fresh id range contains a previously added range, 
-                // expand the range.
-                if fresh_ids[i].0 <= next_fresh_ids[j].0 && fresh_ids[i].1 >= next_fresh_ids[j].1 {
-                    next_fresh_ids[j].0 = fresh_ids[i].0;
-                    next_fresh_ids[j].1 = fresh_ids[i].1;
-                    merged = true;
-                    break;
-                } 
-                // If the fresh id range is contained by a previously added 
-                // range, do not expand the range.
-                if fresh_ids[i].0 >= next_fresh_ids[j].0 && fresh_ids[i].1 <= next_fresh_ids[j].1 {
-                    merged = true;
-                    break;
-                } 
-                // If the fresh id range overlaps a previously added range 
-                // on the left, expand the range to the left.
-                if fresh_ids[i].0 <= next_fresh_ids[j].0 && fresh_ids[i].1 >= next_fresh_ids[j].0 && fresh_ids[i].1 <= next_fresh_ids[j].1 {
-                    next_fresh_ids[j].0 = fresh_ids[i].0;
-                    merged = true;
-                    break;
-                } 
-                // If the fresh id range overlaps a previously added range 
-                // on the right, expand the range to the right.
-                if fresh_ids[i].0 >= next_fresh_ids[j].0 && fresh_ids[i].0 <= next_fresh_ids[j].1 && fresh_ids[i].1 >= next_fresh_ids[j].1 {
-                    next_fresh_ids[j].1 = fresh_ids[i].1;
-                    merged = true;
+    /// Count fresh ingredients
+    fn part1(&self) -> Result<Answer, Error> {
+        let mut part1:usize = 0;
+        for &ingredient in self.ingredients.iter() {
+            for j in 0..self.fresh_ids.len() {
+                if ingredient >= self.fresh_ids[j].0 && ingredient <= self.fresh_ids[j].1 {
+                    part1 += 1;
                     break;
-                } 
+                }
             }
-            if merged {
-                merges += 1;
+        }
+        return Ok(Answer::from(part1));
+    }
+    /// Merge fresh id ranges until no more merges are possible, then sum the 
+    /// range widths
+    fn part2(&self) -> Result<Answer, Error> {
+        let mut part2:usize = 0;
+        let mut fresh_ids = self.fresh_ids.clone();
+        loop {
+            let mut next_fresh_ids:Vec<(usize, usize)> = Vec::new();
+            let mut merges = 0;
+            for i in 0..fresh_ids.len() {
+                let mut merged = false;
+                for j in 0..next_fresh_ids.len() {
+                    // If the fresh id range contains a previously added range, 
+                    // expand the range.
+                    if fresh_ids[i].0 <= next_fresh_ids[j].0 && fresh_ids[i].1 >= next_fresh_ids[j].1 {
+                        next_fresh_ids[j].0 = fresh_ids[i].0;
+                        next_fresh_ids[j].1 = fresh_ids[i].1;
+                        merged = true;
+                        break;
+                    } 
+                    // If the fresh id range is contained by a previously added 
+                    // range, do not expand the range.
+                    if fresh_ids[i].0 >= next_fresh_ids[j].0 && fresh_ids[i].1 <= next_fresh_ids[j].1 {
+                        merged = true;
+                        break;
+                    } 
+                    // If the fresh id range overlaps a previously added range 
+                    // on the left, expand the range to the left.
+                    if fresh_ids[i].0 <= next_fresh_ids[j].0 && fresh_ids[i].1 >= next_fresh_ids[j].0 && fresh_ids[i].1 <= next_fresh_ids[j].1 {
+                        next_fresh_ids[j].0 = fresh_ids[i].0;
+                        merged = true;
+                        break;
+                    } 
+                    // If the fresh id range overlaps a previously added range 
+                    // on the right, expand the range to the right.
+                    if fresh_ids[i].0 >= next_fresh_ids[j].0 && fresh_ids[i].0 <= next_fresh_ids[j].1 && fresh_ids[i].1 >= next_fresh_ids[j].1 {
+                        next_fresh_ids[j].1 = fresh_ids[i].1;
+                        merged = true;
+                        break;
+                    } 
+                }
+                if merged {
+                    merges += 1;
+                }
+                else {
+                    next_fresh_ids.push(fresh_ids[i]);
+                }
             }
-            else {
-                next_fresh_ids.push(fresh_ids[i]);
+            fresh_ids = next_fresh_ids;
+            if merges == 0 {
+                break;
             }
         }
-        fresh_ids = next_fresh_ids;
-        if merges == 0 {
-            break;
+        for i in 0..fresh_ids.len() {
+            part2 +=  fresh_ids[i].1 -fresh_ids[i].0 + 1;
         }
+        return Ok(Answer::from(part2));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util;
+
+    fn example() -> Day5 {
+        let lines = util::read_lines(include_str!("../testdata/day5.txt").as_bytes()).unwrap();
+        return Day5::parse(&lines).unwrap();
+    }
+    #[test]
+    fn part1_example() {
+        assert_eq!(example().part1(), Ok(Answer::Int(3)));
+    }
+    #[test]
+    fn part2_example() {
+        assert_eq!(example().part2(), Ok(Answer::Int(14)));
     }
-    for i in 0..fresh_ids.len() {
-        part2 +=  fresh_ids[i].1 -fresh_ids[i].0 + 1;
+    #[test]
+    fn missing_separator() {
+        let lines = vec!["3-5".to_string(), "10-14".to_string()];
+        assert!(matches!(Day5::parse(&lines), Err(Error::Input(_))));
     }
-    println!("Part 1: {}", part1);
-    println!("Part 2: {}", part2);
 }
@@ -1,82 +1,154 @@
-use crate::util;
+use crate::util::{BigInt, Grid, Integer};
+use crate::error::Error;
+use crate::solution::{self, Answer, Params, Solution};
+use std::str::FromStr;
 
-/// Print the solutions to day 6 for the given input `lines`
-pub fn run(lines:&Vec<String>) {
-    let mut part1 = 0;
-    let mut part2 = 0;
-    // Part 1 - perform the operations with numbers read left-to-right.
-    // Parse the numbers in each row.
-    let mut numbers:Vec<Vec<isize>> = Vec::new();
-    for i in 0..lines.len() - 1 {
-        numbers.push(util::ints_in_string(&lines[i]));
-    }
-    // Extract the operators
-    let operators:Vec<&str> = lines[lines.len() - 1].split_whitespace().collect();
-    // Perform each operation in sequence and sum the result into part1.
-    for i in 0..operators.len() {
-        if operators[i] == "+" {
-            let mut sum = 0;
-            for j in 0..numbers.len() {
-                sum += numbers[j][i];
+pub struct Day6 {
+    /// Numbers in each row, read left-to-right, as strings of digits
+    numbers:Vec<Vec<String>>,
+    /// Operator for each problem
+    operators:Vec<String>,
+    /// All characters in the input, with short lines padded with spaces
+    grid:Grid<char>,
+    /// Compute the grand totals with arbitrary precision (param `bigint`)
+    bigint:bool
+}
+impl Day6 {
+    /// Sum the results of the `problems`, each given as an operator and its
+    /// numbers, as a `T`
+    fn total<T: Integer + FromStr>(problems:&Vec<(char, Vec<String>)>) -> Result<T, Error> {
+        let overflow = || Error::Overflow(format!("the grand total does not fit in {}", std::any::type_name::<T>()));
+        let mut total = T::zero();
+        for (operator, numbers) in problems {
+            let mut result = if *operator == '*' { T::one() } else { T::zero() };
+            for number in numbers {
+                let n = number.parse::<T>().map_err(|_| overflow())?;
+                let next = if *operator == '*' { result.checked_mul(&n) } else { result.checked_add(&n) };
+                result = next.ok_or_else(overflow)?;
             }
-            part1 += sum;
+            total = total.checked_add(&result).ok_or_else(overflow)?;
         }
-        else {
-            let mut product = 1;
-            for j in 0..numbers.len() {
-                product *= numbers[j][i];
-            }
-            part1 += product;
+        return Ok(total);
+    }
+    /// The grand total of the `problems` as an answer
+    fn answer(&self, problems:&Vec<(char, Vec<String>)>) -> Result<Answer, Error> {
+        if self.bigint {
+            return Ok(Answer::from(Day6::total::<BigInt>(problems)?));
         }
+        return Ok(Answer::from(Day6::total::<i64>(problems).map_err(solution::suggest_bigint)?));
     }
-    // Part 2 - perform the operations with the numbers read top-to-bottom.
-    // Read all characters in the input into a grid.
-    let grid = util::read_grid(lines);
-    let width = grid[0].len();
-    let height = grid.len();
-    let operator_line = height-1;
-    let mut operator_index = 0;
-    loop {
-        // Get width of operation by finding the index of the next operator 
-        // or the end of the line.
-        let mut op_width = width - operator_index;
-        for i in (operator_index + 1)..width {
-            if grid[operator_line][i] != ' ' {
-                op_width = i - operator_index - 1;
-                break;
+}
+impl Solution for Day6 {
+    fn parse(lines:&Vec<String>) -> Result<Day6, Error> {
+        return Day6::parse_with(lines, &Params::new());
+    }
+    fn parse_with(lines:&Vec<String>, params:&Params) -> Result<Day6, Error> {
+        let bigint = params.get("bigint", false)?;
+        if lines.len() < 2 {
+            return Err(Error::Input("expected rows of numbers followed by a row of operators".to_string()));
+        }
+        let operator_line = lines.len() - 1;
+        // Check that the operator row only contains operators, and starts 
+        // with one.
+        for (j, c) in lines[operator_line].chars().enumerate() {
+            if c != '+' && c != '*' && c != ' ' {
+                return Err(Error::parse_at(operator_line, j, &format!("expected an operator + or *, found '{}'", c)));
             }
         }
-        // Determine the operation type and initialize an accumulator. 
-        // 1 for products, 0 for sums.
-        let operator = grid[operator_line][operator_index];
-        let mut accumulator = 0;
-        if operator == '*' {
-            accumulator = 1;
+        if !lines[operator_line].starts_with(['+', '*']) {
+            return Err(Error::parse_at(operator_line, 0, "operator row must start with an operator"));
         }
-        // Compose the number in each column and add/multiply with the 
-        // accumulator.
-        for col in (operator_index..operator_index + op_width).rev() {
-            let mut num = 0;
-            for row in 0..height-1 {
-                if grid[row][col] != ' ' {
-                    num *= 10;
-                    num += grid[row][col].to_string().parse::<isize>().unwrap();
+        // Extract the operators
+        let operators:Vec<String> = lines[operator_line].split_whitespace().map(|x| x.to_string()).collect();
+        // Parse the numbers in each row.
+        let mut numbers:Vec<Vec<String>> = Vec::new();
+        for i in 0..operator_line {
+            for (j, c) in lines[i].chars().enumerate() {
+                if !c.is_ascii_digit() && c != ' ' {
+                    return Err(Error::parse_at(i, j, &format!("expected a digit, found '{}'", c)));
                 }
             }
-            if operator == '*' {
-                accumulator *= num;
+            let row:Vec<String> = lines[i].split_whitespace().map(|x| x.to_string()).collect();
+            if row.len() != operators.len() {
+                return Err(Error::parse_at(i, 0, &format!("expected {} numbers to match the operators, found {}", operators.len(), row.len())));
             }
-            else {
-                accumulator += num;
+            numbers.push(row);
+        }
+        // Read all characters in the input into a grid for part 2.
+        let grid = Grid::from_lines_padded(lines, ' ');
+        return Ok(Day6 { numbers, operators, grid, bigint });
+    }
+    /// Perform the operations with numbers read left-to-right.
+    fn part1(&self) -> Result<Answer, Error> {
+        let mut problems = Vec::new();
+        for i in 0..self.operators.len() {
+            let operator = self.operators[i].chars().next().unwrap();
+            problems.push((operator, self.numbers.iter().map(|row| row[i].clone()).collect()));
+        }
+        return self.answer(&problems);
+    }
+    /// Perform the operations with the numbers read top-to-bottom.
+    fn part2(&self) -> Result<Answer, Error> {
+        let mut problems = Vec::new();
+        let grid = &self.grid;
+        let width = grid.width();
+        let Some(operators) = grid.height().checked_sub(1).and_then(|y| grid.row(y)) else {
+            return Err(Error::Input("expected a row of operators".to_string()));
+        };
+        let mut operator_index = 0;
+        loop {
+            // Get width of operation by finding the index of the next operator 
+            // or the end of the line.
+            let mut op_width = width - operator_index;
+            for i in (operator_index + 1)..width {
+                if operators[i] != ' ' {
+                    op_width = i - operator_index - 1;
+                    break;
+                }
+            }
+            // Compose the number in each column from its digits, right to left
+            let mut numbers = Vec::new();
+            for col in (operator_index..operator_index + op_width).rev() {
+                let digits:String = grid.column(col).into_iter().flatten().filter(|c| c.is_ascii_digit()).collect();
+                numbers.push(if digits.is_empty() { "0".to_string() } else { digits });
+            }
+            problems.push((operators[operator_index], numbers));
+            // Move to the next operation or end
+            operator_index += op_width + 1;
+            if operator_index >= width {
+                break;
             }
-        }        
-        part2 += accumulator;
-        // Move to the next operation or end
-        operator_index += op_width + 1;
-        if operator_index > width {
-            break;
         }
+        return self.answer(&problems);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util;
+
+    fn example() -> Day6 {
+        let lines = util::read_lines(include_str!("../testdata/day6.txt").as_bytes()).unwrap();
+        return Day6::parse(&lines).unwrap();
+    }
+    #[test]
+    fn part1_example() {
+        assert_eq!(example().part1(), Ok(Answer::Int(4277556)));
+    }
+    #[test]
+    fn part2_example() {
+        assert_eq!(example().part2(), Ok(Answer::Int(3263827)));
+    }
+    #[test]
+    fn bigint() {
+        let lines:Vec<String> = ["99999999999", "99999999999", "99999999999", "*          "].iter().map(|l| l.to_string()).collect();
+        assert!(matches!(Day6::parse(&lines).unwrap().part1(), Err(Error::Overflow(_))));
+        let mut params = Params::new();
+        params.insert("bigint", "true");
+        let day6 = Day6::parse_with(&lines, &params).unwrap();
+        assert_eq!(day6.part1(), Ok(Answer::from("999999999970000000000299999999999".parse::<BigInt>().unwrap())));
+        // Each column reads 999
+        assert_eq!(day6.part2(), Ok(Answer::from(BigInt::from(999).pow(11))));
     }
-    println!("Part 1: {}", part1);
-    println!("Part 2: {}", part2);
 }
@@ -1,39 +1,87 @@
-use crate::util;
+use crate::util::{Grid, Vec2};
+use crate::error::Error;
+use crate::solution::{Answer, Solution};
 
-/// Print the solutions to day 7 for the given input `lines`
-pub fn run(lines:&Vec<String>) {
-    let mut part1 = 0;
-    // Read the grid
-    let grid = util::read_grid(lines);
-    let width = grid[0].len();
-    let height = grid.len();
-    // Get the S position
-    let s_x = lines[0].find("S").unwrap();
-    // Keep a sum of total beams that reach each x position
-    let mut beams:Vec<usize> = vec![0;width];
-    beams[s_x] = 1;
-    for y in 1..height {
-        // Update the beams at each x position at this depth of the grid.
-        let mut next_beams = vec![0;width];
-        for x in 0..width {
-            if grid[y][x] == '^' {
-                // If a beam reaches this splitter, increment the part 1 count.
-                if beams[x] > 0 {
-                    part1 += 1;
-                }
-                if x > 0 {
-                    next_beams[x-1] += beams[x];
+pub struct Day7 {
+    grid:Grid<char>,
+    /// Horizontal position of the beam start `S`
+    s_x:usize
+}
+impl Day7 {
+    /// Follow the beams down the grid, returning the number of splitters 
+    /// reached and the total number of beams that reach the bottom of the grid
+    fn trace(&self) -> (usize, usize) {
+        let mut splits = 0;
+        let width = self.grid.width();
+        let height = self.grid.height();
+        // Keep a sum of total beams that reach each x position
+        let mut beams:Vec<usize> = vec![0;width];
+        beams[self.s_x] = 1;
+        for y in 1..height {
+            // Update the beams at each x position at this depth of the grid.
+            let mut next_beams = vec![0;width];
+            for x in 0..width {
+                if self.grid[Vec2::newu(x, y)] == '^' {
+                    // If a beam reaches this splitter, increment the split 
+                    // count.
+                    if beams[x] > 0 {
+                        splits += 1;
+                    }
+                    if x > 0 {
+                        next_beams[x-1] += beams[x];
+                    }
+                    if x < width - 1 {
+                        next_beams[x+1] += beams[x];
+                    }
                 }
-                if x < width - 1 {
-                    next_beams[x+1] += beams[x];
+                else {
+                    next_beams[x] += beams[x];
                 }
             }
-            else {
-                next_beams[x] += beams[x];
-            }
+            beams = next_beams;
         }
-        beams = next_beams;
+        return (splits, beams.iter().sum::<usize>());
+    }
+}
+impl Solution for Day7 {
+    fn parse(lines:&Vec<String>) -> Result<Day7, Error> {
+        // Read the grid
+        let grid = Grid::from_lines(lines)?;
+        // Get the S position
+        let s_x = match grid.row(0).and_then(|row| row.iter().position(|&c| c == 'S')) {
+            Some(x) => x,
+            None => return Err(Error::parse_at(0, 0, "no beam start S found in the first row"))
+        };
+        return Ok(Day7 { grid, s_x });
+    }
+    fn part1(&self) -> Result<Answer, Error> {
+        return Ok(Answer::from(self.trace().0));
+    }
+    fn part2(&self) -> Result<Answer, Error> {
+        return Ok(Answer::from(self.trace().1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util;
+
+    fn example() -> Day7 {
+        let lines = util::read_lines(include_str!("../testdata/day7.txt").as_bytes()).unwrap();
+        return Day7::parse(&lines).unwrap();
+    }
+    #[test]
+    fn part1_example() {
+        assert_eq!(example().part1(), Ok(Answer::Int(21)));
+    }
+    #[test]
+    fn part2_example() {
+        assert_eq!(example().part2(), Ok(Answer::Int(40)));
+    }
+    #[test]
+    fn missing_start() {
+        let lines = vec!["...".to_string(), ".^.".to_string()];
+        assert!(matches!(Day7::parse(&lines), Err(Error::Parse { line:1, column:1, .. })));
     }
-    println!("Part 1: {}", part1);
-    println!("Part 2: {}", beams.iter().sum::<usize>());
 }
@@ -1,87 +1,149 @@
 use crate::util;
-use crate::util::Vec3;
-use std::collections::HashSet;
+use crate::util::{BigInt, Integer, KdTree, UnionFind, Vec3};
+use crate::error::Error;
+use crate::solution::{self, Answer, Params, Solution};
 
-pub fn merge(groups:&mut Vec<HashSet<usize>>, a:usize, b:usize) {
-    let mut a_group_index:Option<usize> = None;
-    let mut b_group_index:Option<usize> = None;
-    for i in 0..groups.len() {
-        if groups[i].contains(&a) {
-            a_group_index = Some(i);
-        }
-        if groups[i].contains(&b) {
-            b_group_index = Some(i);
+/// Default number of shortest connections made for part 1
+pub const DEFAULT_CONNECTIONS:usize = 1000;
+/// Default number of largest circuits multiplied for part 1
+pub const DEFAULT_TOP_K:usize = 3;
+
+pub struct Day8 {
+    /// Spatial index of the positions, used to find connections in order of
+    /// ascending distance
+    tree:KdTree<Vec3>,
+    /// Number of shortest connections made for part 1 (param `connections`)
+    connections:usize,
+    /// Number of largest circuits whose sizes are multiplied for part 1 
+    /// (param `top_k`)
+    top_k:usize,
+    /// Compute the part 1 product with arbitrary precision (param `bigint`)
+    bigint:bool
+}
+impl Day8 {
+    /// The product of the circuit `sizes` as a `T`
+    fn product<T: Integer>(sizes:&[usize]) -> Result<T, Error> {
+        let mut product = T::one();
+        for size in sizes {
+            product = match product.checked_mul(&T::from_i64(*size as i64)) {
+                Some(p) => p,
+                None => return Err(Error::Overflow(format!("the product of the circuit sizes does not fit in {}", std::any::type_name::<T>())))
+            };
         }
+        return Ok(product);
     }
-    assert!(!a_group_index.is_none(), "Merge of indexes {}, {} failed. {} not found in groups", a, b, a);
-    assert!(!b_group_index.is_none(), "Merge of indexes {}, {} failed. {} not found in groups", a, b, b);
-    // If a,b are already in the same group, done
-    if a_group_index == b_group_index {
-        return;
+}
+impl Solution for Day8 {
+    fn parse(lines:&Vec<String>) -> Result<Day8, Error> {
+        return Day8::parse_with(lines, &Params::new());
     }
-    // If a,b are in separate groups, remove one group and add it to the other
-    else {
-        let b_group = groups.remove(b_group_index.unwrap());
-        if a_group_index.unwrap() > b_group_index.unwrap() {
-            groups[a_group_index.unwrap() - 1].extend(b_group);
+    fn parse_with(lines:&Vec<String>, params:&Params) -> Result<Day8, Error> {
+        let connections = params.get("connections", DEFAULT_CONNECTIONS)?;
+        let top_k = params.get("top_k", DEFAULT_TOP_K)?;
+        let bigint = params.get("bigint", false)?;
+        if top_k == 0 {
+            return Err(Error::Param("top_k must be at least 1".to_string()));
+        }
+        let numbers = util::ints_in_strings(lines);
+        let mut positions = Vec::new();
+        for i in 0..numbers.len() {
+            if numbers[i].len() != 3 {
+                return Err(Error::parse_at(i, 0, &format!("expected 3 coordinates, found {}", numbers[i].len())));
+            }
+            positions.push(Vec3::new(numbers[i][0],numbers[i][1],numbers[i][2]));
         }
-        else {
-            groups[a_group_index.unwrap()].extend(b_group);    
+        if positions.len() < 2 {
+            return Err(Error::Input("at least 2 junction boxes are required".to_string()));
         }
+        let tree = KdTree::new(&positions);
+        return Ok(Day8 { tree, connections, top_k, bigint });
     }
-}
-
-/// Print the solutions to day 8 for the given input `lines`
-pub fn run(lines:&Vec<String>) {
-    let mut part1 = 1;
-    let mut part2 = 0;
-    let numbers = util::ints_in_strings(lines);
-    let mut positions = Vec::new();
-    for i in 0..numbers.len() {
-        positions.push(Vec3::new(numbers[i][0],numbers[i][1],numbers[i][2]));
+    fn part1(&self) -> Result<Answer, Error> {
+        // Start with one group per position
+        let mut groups = UnionFind::new(self.tree.len());
+        // Merge groups by adding connections in shortest connection order up 
+        // to the part 1 limit
+        for (_, a, b) in self.tree.closest_pairs().take(self.connections) {
+            groups.union(a, b);
+        }
+        // Get the sizes of the `top_k` largest groups
+        let mut lengths = groups.component_sizes();
+        lengths.sort();
+        lengths.reverse();
+        let largest = &lengths[..lengths.len().min(self.top_k)];
+        if self.bigint {
+            return Ok(Answer::from(Day8::product::<BigInt>(largest)?));
+        }
+        return Ok(Answer::from(Day8::product::<i64>(largest).map_err(solution::suggest_bigint)?));
     }
-    // Construct a list distances between all positions then sort by ascending distance
-    let mut dists:Vec<(usize, usize, usize)> = Vec::new();
-    for i in 0..positions.len() {
-        for j in i+1..positions.len() {
-            dists.push(((positions[i] - positions[j]).len_squared() as usize, i, j));
+    fn part2(&self) -> Result<Answer, Error> {
+        let mut part2:i128 = 0;
+        let mut groups = UnionFind::new(self.tree.len());
+        // Merge groups in shortest connection order until all are connected 
+        // and record the product of the x coordinates for the final connected
+        // positions
+        for (_, a, b) in self.tree.closest_pairs() {
+            groups.union(a, b);
+            if groups.components() == 1 {
+                part2 = self.tree.point(a).x as i128 * self.tree.point(b).x as i128;
+                break;
+            }
         }
+        return Ok(Answer::from(part2));
     }
-    dists.sort_by(|x, y| (x.0).cmp(&y.0));
-    // Set limit of connections to add for part 1
-    let connection_limit = if positions.len() == 20 {10} else {1000};
-    // Construct a list of groups, one per position
-    let mut groups:Vec<HashSet<usize>> = Vec::new();
-    for i in 0..positions.len() {
-        groups.push(HashSet::from([i]));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
+    /// The example makes 10 connections rather than 1000
+    fn example() -> Day8 {
+        let lines = util::read_lines(include_str!("../testdata/day8.txt").as_bytes()).unwrap();
+        return Day8::parse_with(&lines, &Params::from_args(&vec!["connections=10".to_string()]).unwrap()).unwrap();
     }
-    // Merge groups by adding connections in shortest connection order up to 
-    // the part 1 limit
-    for i in 0..connection_limit {
-        let (_,a,b) = dists[i];
-        merge(&mut groups, a, b);
+    #[test]
+    fn part1_example() {
+        assert_eq!(example().part1(), Ok(Answer::Int(40)));
     }
-    // Get the sizes of the 3 largest groups
-    let mut lengths = Vec::new();
-    for i in 0..groups.len() {
-        lengths.push(groups[i].len());
+    #[test]
+    fn part2_example() {
+        assert_eq!(example().part2(), Ok(Answer::Int(25272)));
     }
-    lengths.sort();
-    lengths.reverse();
-    for i in 0..3 {
-        part1 *= lengths[i];
+    #[test]
+    fn params() {
+        let lines = util::read_lines(include_str!("../testdata/day8.txt").as_bytes()).unwrap();
+        let mut params = Params::new();
+        params.insert("connections", "10");
+        params.insert("top_k", "1");
+        assert_eq!(Day8::parse_with(&lines, &params).unwrap().part1(), Ok(Answer::Int(5)));
+        // All boxes end up in one circuit with the default 1000 connections
+        assert_eq!(Day8::parse(&lines).unwrap().part1(), Ok(Answer::Int(20)));
+        params.insert("top_k", "0");
+        assert!(matches!(Day8::parse_with(&lines, &params), Err(Error::Param(_))));
+        params.insert("connections", "ten");
+        assert!(matches!(Day8::parse_with(&lines, &params), Err(Error::Param(_))));
     }
-    // Continue merging groups until all are connected and record the product
-    // of the x coordinates for the final connected positions
-    for i in connection_limit..dists.len() {
-        let (_, a,b) = dists[i];
-        merge(&mut groups, a, b);
-        if groups.len() == 1 {
-            part2 = positions[a].x * positions[b].x;
-            break;
+    #[test]
+    fn bigint() {
+        // 20 far apart rows of 10 boxes, each joined into a circuit by its 9
+        // shortest connections, so the product of all sizes is 10^20
+        let mut lines = Vec::new();
+        for row in 0..20 {
+            for x in 0..10 {
+                lines.push(format!("{},{},0", x, row * 1000));
+            }
         }
+        let mut params = Params::new();
+        params.insert("connections", "180");
+        params.insert("top_k", "20");
+        assert!(matches!(Day8::parse_with(&lines, &params).unwrap().part1(), Err(Error::Overflow(_))));
+        params.insert("bigint", "true");
+        assert_eq!(Day8::parse_with(&lines, &params).unwrap().part1(), Ok(Answer::Int(10i128.pow(20))));
+    }
+    #[test]
+    fn missing_coordinate() {
+        let lines = vec!["162,817,812".to_string(), "57,618".to_string()];
+        assert!(matches!(Day8::parse(&lines), Err(Error::Parse { line:2, column:1, .. })));
     }
-    println!("Part 1: {}", part1);
-    println!("Part 2: {}", part2);
 }
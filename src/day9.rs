@@ -2,6 +2,8 @@ use crate::util;
 use crate::util::Vec2;
 use std::collections::{HashSet,HashMap};
 use std::cmp::{min,max};
+use crate::error::Error;
+use crate::solution::{Answer, Solution};
 
 /// Ray cast from left to `pos` and count crossings of polygon horizontal line
 /// segments to determine if the coordinate is inside or outside the polygon 
@@ -130,76 +132,125 @@ fn insert_sorted_isize(vec:&mut Vec<isize>, val:isize) {
 fn area(pos1:&Vec2, pos2:&Vec2) -> isize {
     return ((pos1.x - pos2.x).abs() + 1) * ((pos1.y - pos2.y).abs() + 1);
 }
-/// Print the solutions to day 9 for the given input `lines`
-pub fn run(lines:&Vec<String>) {
-    let mut part1 = 0;
-    let mut part2 = 0;
-
-    // List of tile positions for consistant iteration
-    let mut red_tiles = Vec::new();
-    // Set of tile positions for quick queries
-    let mut red_tiles_set = HashSet::new();
-    // Sorted lists of y coordinates of red lights indexed by x coordinate
-    let mut positions_by_x:HashMap<isize, Vec<isize>> = HashMap::new();
-    // Sorted lists of x coordinates of red lights indexed by y coordinate
-    let mut positions_by_y:HashMap<isize, Vec<isize>> = HashMap::new();
-    
-    // Read the input and populate the collections of tile position information
-    let numbers = util::ints_in_strings(lines);
-    for i in 0..numbers.len() {
-        let red_tile = util::Vec2::new(numbers[i][0], numbers[i][1]);
-        red_tiles.push(red_tile);
-        red_tiles_set.insert(red_tile);
-        // For each distinct horizontal coordinate, build a lookup of the 
-        // sorted vertical coordinates.
-        if positions_by_x.contains_key(&red_tile.x) {
-            insert_sorted_isize(positions_by_x.get_mut(&red_tile.x).unwrap(), red_tile.y);
+pub struct Day9 {
+    /// List of tile positions for consistant iteration
+    red_tiles:Vec<Vec2>,
+    /// Set of tile positions for quick queries
+    red_tiles_set:HashSet<Vec2>,
+    /// Sorted lists of y coordinates of red lights indexed by x coordinate
+    positions_by_x:HashMap<isize, Vec<isize>>,
+    /// Sorted lists of x coordinates of red lights indexed by y coordinate
+    positions_by_y:HashMap<isize, Vec<isize>>,
+    /// All horizontal coordinates of tiles in sorted order
+    ordered_x_coords:Vec<isize>,
+    /// All vertical coordinates of tiles in sorted order
+    ordered_y_coords:Vec<isize>
+}
+impl Day9 {
+    /// Test if the rectangle perimeter segment from `pos1` to `pos2` is 
+    /// entirely inside the polygon
+    fn segment_in_polygon(&self, pos1:Vec2, pos2:Vec2) -> bool {
+        return perimeter_segment_in_polygon(pos1, pos2, &self.red_tiles_set, &self.positions_by_x, &self.positions_by_y, &self.ordered_x_coords, &self.ordered_y_coords);
+    }
+}
+impl Solution for Day9 {
+    fn parse(lines:&Vec<String>) -> Result<Day9, Error> {
+        let mut red_tiles = Vec::new();
+        let mut red_tiles_set = HashSet::new();
+        let mut positions_by_x:HashMap<isize, Vec<isize>> = HashMap::new();
+        let mut positions_by_y:HashMap<isize, Vec<isize>> = HashMap::new();
+        
+        // Read the input and populate the collections of tile position 
+        // information
+        let numbers = util::ints_in_strings(lines);
+        for i in 0..numbers.len() {
+            if numbers[i].len() != 2 {
+                return Err(Error::parse_at(i, 0, &format!("expected 2 coordinates, found {}", numbers[i].len())));
+            }
+            let red_tile = util::Vec2::new(numbers[i][0], numbers[i][1]);
+            red_tiles.push(red_tile);
+            red_tiles_set.insert(red_tile);
+            // For each distinct horizontal coordinate, build a lookup of the 
+            // sorted vertical coordinates.
+            insert_sorted_isize(positions_by_x.entry(red_tile.x).or_default(), red_tile.y);
+            // For each distinct vertical coordinate, build a lookup of the 
+            // sorted horizontal coordinates
+            insert_sorted_isize(positions_by_y.entry(red_tile.y).or_default(), red_tile.x);
         }
-        else {
-            positions_by_x.insert(red_tile.x, vec![red_tile.y]);
+
+        // The solution relies on every row and column containing either 0 or
+        // 2 tiles
+        for i in 0..red_tiles.len() {
+            if positions_by_x[&red_tiles[i].x].len() != 2 || positions_by_y[&red_tiles[i].y].len() != 2 {
+                return Err(Error::parse_at(i, 0, &format!("tile {} must share its row and column with exactly one other tile", red_tiles[i])));
+            }
         }
-        // For each distinct vertical coordinate, build a lookup of the 
-        // sorted horizontal coordinates
-        if positions_by_y.contains_key(&red_tile.y) {
-            insert_sorted_isize(positions_by_y.get_mut(&red_tile.y).unwrap(), red_tile.x);
+
+        // Get all horizontal and vertical coordinates of tiles in sorted 
+        // orders
+        let mut ordered_x_coords:Vec<isize> = positions_by_x.keys().cloned().collect();
+        ordered_x_coords.sort();
+        let mut ordered_y_coords:Vec<isize> = positions_by_y.keys().cloned().collect();
+        ordered_y_coords.sort();
+        return Ok(Day9 { red_tiles, red_tiles_set, positions_by_x, positions_by_y, ordered_x_coords, ordered_y_coords });
+    }
+    /// Find the greatest area of the rectangle for each pair of tiles
+    fn part1(&self) -> Result<Answer, Error> {
+        let mut part1 = 0;
+        for i in 0..self.red_tiles.len() {
+            for j in i+1..self.red_tiles.len() {
+                let area = area(&self.red_tiles[i], &self.red_tiles[j]);
+                if area > part1 {
+                    part1 = area;
+                }
+            }
         }
-        else {
-            positions_by_y.insert(red_tile.y, vec![red_tile.x]);
+        return Ok(Answer::from(part1));
+    }
+    /// Find the area of the rectangle for each pair of tiles and test if it's
+    /// entirely contained in the polygon. Record the greatest area contained 
+    /// in the polygon.
+    fn part2(&self) -> Result<Answer, Error> {
+        let mut part2 = 0;
+        let red_tiles = &self.red_tiles;
+        for i in 0..red_tiles.len() {
+            for j in i+1..red_tiles.len() {
+                let area = area(&red_tiles[i], &red_tiles[j]);
+                if area > part2 {
+                    // Test if each perimeter line segment of the rectangle is 
+                    // entirely inside the polygon using a ray-casting 
+                    // approach. If so, the rectangle is within the polygon.
+                    let min_x = min(red_tiles[i].x, red_tiles[j].x);
+                    let max_x = max(red_tiles[i].x, red_tiles[j].x);
+                    let min_y = min(red_tiles[i].y, red_tiles[j].y);
+                    let max_y = max(red_tiles[i].y, red_tiles[j].y);
+                    if self.segment_in_polygon(Vec2::new(min_x, min_y), Vec2::new(min_x, max_y)) &&
+                        self.segment_in_polygon(Vec2::new(min_x, min_y), Vec2::new(max_x, min_y)) &&
+                        self.segment_in_polygon(Vec2::new(max_x, min_y), Vec2::new(max_x, max_y)) &&
+                        self.segment_in_polygon(Vec2::new(min_x, max_y), Vec2::new(max_x, max_y)) {
+                        part2 = area; 
+                    }
+                }            
+            }
         }
+        return Ok(Answer::from(part2));
     }
+}
 
-    // Get all horizontal and vertical coordinates of tiles in sorted orders
-    let mut ordered_x_coords:Vec<isize> = positions_by_x.keys().cloned().collect();
-    ordered_x_coords.sort();
-    let mut ordered_y_coords:Vec<isize> = positions_by_y.keys().cloned().collect();
-    ordered_y_coords.sort();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    // Find the area of the rectangle for each pair of tiles and test if it's
-    // entirely contained in the polygon. Record the greatest overall area and
-    // the greatest area contained in the polygon.
-    for i in 0..red_tiles.len() {
-        for j in i+1..red_tiles.len() {
-            let area = area(&red_tiles[i], &red_tiles[j]);
-            if area > part1 {
-                part1 = area;
-            }
-            if area > part2 {
-                // Test if each perimeter line segment of the rectangle is 
-                // entirely inside the polygon using a ray-casting approach. If 
-                // so, the rectangle is within the polygon.
-                let min_x = min(red_tiles[i].x, red_tiles[j].x);
-                let max_x = max(red_tiles[i].x, red_tiles[j].x);
-                let min_y = min(red_tiles[i].y, red_tiles[j].y);
-                let max_y = max(red_tiles[i].y, red_tiles[j].y);
-                if perimeter_segment_in_polygon(Vec2::new(min_x, min_y), Vec2::new(min_x, max_y), &red_tiles_set, &positions_by_x, &positions_by_y, &ordered_x_coords, &ordered_y_coords) &&
-                    perimeter_segment_in_polygon(Vec2::new(min_x, min_y), Vec2::new(max_x, min_y), &red_tiles_set, &positions_by_x, &positions_by_y, &ordered_x_coords, &ordered_y_coords) &&
-                    perimeter_segment_in_polygon(Vec2::new(max_x, min_y), Vec2::new(max_x, max_y), &red_tiles_set, &positions_by_x, &positions_by_y, &ordered_x_coords, &ordered_y_coords) &&
-                    perimeter_segment_in_polygon(Vec2::new(min_x, max_y), Vec2::new(max_x, max_y), &red_tiles_set, &positions_by_x, &positions_by_y, &ordered_x_coords, &ordered_y_coords) {
-                    part2 = area; 
-                }
-            }            
-        }
+    fn example() -> Day9 {
+        let lines = util::read_lines(include_str!("../testdata/day9.txt").as_bytes()).unwrap();
+        return Day9::parse(&lines).unwrap();
+    }
+    #[test]
+    fn part1_example() {
+        assert_eq!(example().part1(), Ok(Answer::Int(50)));
+    }
+    #[test]
+    fn part2_example() {
+        assert_eq!(example().part2(), Ok(Answer::Int(24)));
     }
-    println!("Part 1: {}", part1);
-    println!("Part 2: {}", part2);
 }
@@ -0,0 +1,47 @@
+use std::str::FromStr;
+
+/// Errors produced while reading or parsing puzzle inputs and solving puzzles
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The input could not be read
+    Io(String),
+    /// Malformed input at the given 1-based `line` and `column`
+    Parse { line:usize, column:usize, message:String },
+    /// The input is malformed as a whole (e.g. empty or missing a section)
+    Input(String),
+    /// The input is well-formed but the puzzle has no solution for it
+    NoSolution(String),
+    /// A solver parameter is malformed or out of range
+    Param(String),
+    /// An answer is too large for the solver's integer type
+    Overflow(String)
+}
+impl Error {
+    /// Construct a parse error at the 0-based `line_index` and `column_index`
+    /// of the input
+    pub fn parse_at(line_index:usize, column_index:usize, message:&str) -> Error {
+        return Error::Parse { line:line_index + 1, column:column_index + 1, message:message.to_string() };
+    }
+}
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut  std::fmt::Formatter) ->  std::fmt::Result {
+        match self {
+            Error::Io(message) => write!(f, "{}", message),
+            Error::Parse { line, column, message } => write!(f, "line {}, column {}: {}", line, column, message),
+            Error::Input(message) => write!(f, "invalid input: {}", message),
+            Error::NoSolution(message) => write!(f, "no solution: {}", message),
+            Error::Param(message) => write!(f, "invalid parameter: {}", message),
+            Error::Overflow(message) => write!(f, "overflow: {}", message)
+        }
+    }
+}
+impl std::error::Error for Error {}
+
+/// Parse `s`, found at the 0-based `line_index` and `column_index` of the
+/// input, as a number
+pub fn parse_field<T: FromStr>(s:&str, line_index:usize, column_index:usize) -> Result<T, Error> {
+    match s.parse::<T>() {
+        Ok(x) => return Ok(x),
+        Err(_) => return Err(Error::parse_at(line_index, column_index, &format!("could not parse '{}' as a number", s)))
+    }
+}
@@ -0,0 +1,27 @@
+//! Advent of Code 2025 solutions and utilities.
+//!
+//! Each day's solver implements `solution::Solution` and can be looked up by
+//! day number through `solution::get`. Malformed inputs and unsolvable 
+//! puzzles are reported through `error::Error` rather than panics. The `util`
+//! module contains the shared helper types and functions used by the solvers.
+pub mod util;
+pub mod error;
+pub mod solution;
+pub mod runner;
+pub mod bench;
+// Day modules
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+
+pub use solution::{Answer, Params, Solution};
+pub use error::Error;
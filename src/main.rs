@@ -1,92 +1,383 @@
-pub mod util;
-// Day modules
-pub mod day1;
-pub mod day2;
-pub mod day3;
-pub mod day4;
-pub mod day5;
-pub mod day6;
-pub mod day7;
-pub mod day8;
-pub mod day9;
-pub mod day10;
-pub mod day11;
-pub mod day12;
+use clap::{Arg, ArgAction, Command};
+use std::path::Path;
+use adventofcode2025::{bench, day10, day11, runner, util, Error, Params, Solution};
+use adventofcode2025::solution::{Answer, MAXDAY};
+use adventofcode2025::runner::Check;
 
-use clap::{Arg, Command};
-use std::time::{Instant};
+/// Exit code when checked answers do not match the expected answers, or a
+/// benchmark is slower than the saved results it is compared against
+const EXIT_MISMATCH:i32 = 1;
+/// Exit code for invalid arguments or unreadable input files
+const EXIT_USAGE:i32 = 2;
+/// Exit code for malformed inputs or inputs the solution can't solve
+const EXIT_SOLVER:i32 = 3;
 
-const MAXDAY:usize = 12;
+/// The exit code for a failed run with the given `error`
+fn error_exit_code(error:&Error) -> i32 {
+    match error {
+        Error::Io(_) | Error::Param(_) => return EXIT_USAGE,
+        _ => return EXIT_SOLVER
+    }
+}
+
+/// Parse the DAY argument as either a single day `N` or an inclusive range of
+/// days `A-B`
+fn parse_days(day_arg:&str) -> Result<Vec<usize>, String> {
+    let parts:Vec<&str> = day_arg.split("-").collect();
+    if parts.len() > 2 {
+        return Err(format!("Could not parse DAY argument '{}' as a day or range of days.", day_arg));
+    }
+    let mut bounds = Vec::new();
+    for part in parts {
+        match part.trim().parse::<usize>() {
+            Ok(n) => {
+                if !(1..=MAXDAY).contains(&n) {
+                    return Err(format!("DAY must be in range 1 - {} ({} provided)", MAXDAY, n));
+                }
+                bounds.push(n);
+            },
+            Err(_) => {
+                return Err(format!("Could not parse DAY argument '{}' as an integer.", day_arg));
+            }
+        }
+    }
+    let start = bounds[0];
+    let stop = *bounds.last().unwrap();
+    if start > stop {
+        return Err(format!("DAY range {} is empty", day_arg));
+    }
+    return Ok((start..=stop).collect());
+}
+
+/// Output format for run results
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Text,
+    Json,
+    Csv
+}
+
+/// Print the results of a run in a machine-readable `format`
+fn print_report(results:&Vec<runner::DayResult>, skipped:&Vec<runner::Skipped>, format:Format) {
+    match format {
+        Format::Json => print!("{}", runner::json_report(results, skipped)),
+        Format::Csv => print!("{}", runner::csv_report(results, skipped)),
+        Format::Text => print!("{}", runner::summary_table(results, skipped))
+    }
+}
+
+/// Run a single `day` with solver `params` on the input at `input_path` and
+/// print the answers. If an `answers_path` is given, the answers are checked
+/// against it.
+fn run_single(day:usize, input_path:&str, params:&Params, answers_path:Option<&str>, format:Format) {
+    if format == Format::Text {
+        println!("\nDay:\t{}\nInput:\t{}\n", day, input_path);
+    }
+    let mut result = match runner::run_day_input(day, input_path, params) {
+        Err(e) => {
+            if format == Format::Text {
+                eprintln!("Error: day {}: {}", day, e);
+            }
+            else {
+                print_report(&vec![], &vec![runner::Skipped { day, input_path:input_path.to_string(), reason:e.to_string() }], format);
+            }
+            std::process::exit(error_exit_code(&e));
+        },
+        Ok(result) => result
+    };
+    let mut exit_code = 0;
+    if let Some(path) = answers_path {
+        match runner::read_answers(path) {
+            Err(e) => {
+                result.check = Some(Check::Missing(e));
+                exit_code = EXIT_USAGE;
+            },
+            Ok(expected) => {
+                let check = expected.check(&result);
+                if check != Check::Pass {
+                    exit_code = EXIT_MISMATCH;
+                }
+                result.check = Some(check);
+            }
+        }
+    }
+    if format != Format::Text {
+        print_report(&vec![result], &vec![], format);
+        std::process::exit(exit_code);
+    }
+    println!("Part 1: {}", result.part1);
+    if result.part2 != Answer::None {
+        println!("Part 2: {}", result.part2);
+    }
+    let timings = result.timings;
+    println!();
+    if let Some(io) = timings.io {
+        println!("Input read time:\t{:?}", io);
+    }
+    println!("Parse time:\t\t{:?}", timings.parse);
+    println!("Part 1 time:\t\t{:?}", timings.part1);
+    if result.part2 != Answer::None {
+        println!("Part 2 time:\t\t{:?}", timings.part2);
+    }
+    println!("Total execution time:\t{:?}", timings.total());
+    if let Some(check) = result.check {
+        println!("\nCheck:\t{}", check);
+    }
+    std::process::exit(exit_code);
+}
+
+/// Run each of `days` with solver `params` on its default input and print a
+/// summary table. Days without an input file or whose solution fails are 
+/// skipped. If `check` is set, answers are checked against each day's default
+/// answers file, and a missing or unreadable one is a usage error as it is
+/// for a single day.
+fn run_many(days:&Vec<usize>, params:&Params, check:bool, format:Format) {
+    let mut results = Vec::new();
+    let mut skipped = Vec::new();
+    let mut exit_code = 0;
+    for &day in days {
+        let input_path = runner::default_input_path(day);
+        if !Path::new(&input_path).exists() {
+            skipped.push(runner::Skipped { day, reason:format!("no input at {}", input_path), input_path });
+            continue;
+        }
+        match runner::run_day_input(day, &input_path, params) {
+            Err(e) => {
+                exit_code = exit_code.max(error_exit_code(&e));
+                skipped.push(runner::Skipped { day, input_path, reason:e.to_string() });
+            },
+            Ok(mut result) => {
+                if check {
+                    let answers_path = runner::default_answers_path(&input_path);
+                    result.check = match runner::read_answers(&answers_path) {
+                        Ok(expected) => Some(expected.check(&result)),
+                        Err(e) => {
+                            exit_code = exit_code.max(EXIT_USAGE);
+                            Some(Check::Missing(e))
+                        }
+                    };
+                }
+                results.push(result);
+            }
+        }
+    }
+    if format == Format::Text {
+        println!();
+    }
+    print_report(&results, &skipped, format);
+    if exit_code == 0 && results.iter().any(|r| matches!(r.check, Some(Check::Mismatch(_)))) {
+        exit_code = EXIT_MISMATCH;
+    }
+    std::process::exit(exit_code);
+}
+
+/// Benchmark each of `days` with solver `params` over `runs` runs and print
+/// the statistics. Results are compared against those saved at `compare_path`
+/// and saved to `save_path` if given. Exits with `EXIT_MISMATCH` if the
+/// comparison finds a regression.
+fn run_bench(days:&Vec<usize>, input_arg:Option<&String>, params:&Params, runs:usize, save_path:Option<&String>, compare_path:Option<&String>) {
+    let previous = match compare_path {
+        Some(path) => {
+            match bench::load(path) {
+                Ok(results) => Some(results),
+                Err(e) => {
+                    eprintln!("Error reading benchmark results: {}", e);
+                    std::process::exit(EXIT_USAGE);
+                }
+            }
+        },
+        None => None
+    };
+    let mut results = Vec::new();
+    for &day in days {
+        let input_path = match input_arg {
+            Some(input) => input.clone(),
+            None => runner::default_input_path(day)
+        };
+        // Days without inputs are skipped when benchmarking more than one day
+        if days.len() > 1 && !Path::new(&input_path).exists() {
+            println!("Day {}: skipped, no input at {}", day, input_path);
+            continue;
+        }
+        match util::read_input(&input_path) {
+            Err(e) => {
+                eprintln!("Error: day {}: {}", day, e);
+                std::process::exit(error_exit_code(&e));
+            },
+            Ok(input_lines) => {
+                println!("Day {}: {} runs", day, runs);
+                match bench::bench_day(day, &input_lines, runs, params) {
+                    Ok(result) => results.push(result),
+                    Err(e) => {
+                        eprintln!("Error: day {}: {}", day, e);
+                        std::process::exit(error_exit_code(&e));
+                    }
+                }
+            }
+        }
+    }
+    println!();
+    print!("{}", bench::summary_table(&results, previous.as_ref(), bench::REGRESSION_THRESHOLD));
+    if let Some(path) = save_path {
+        match bench::save(path, &results) {
+            Ok(_) => println!("\nBenchmark results saved to {}", path),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(EXIT_USAGE);
+            }
+        }
+    }
+    if previous.is_some_and(|prev| bench::has_regression(&results, &prev, bench::REGRESSION_THRESHOLD)) {
+        std::process::exit(EXIT_MISMATCH);
+    }
+}
+
+/// Write the day 11 device graph read from `input_path` to `dot_path` in
+/// Graphviz DOT format, optionally labelled with path `counts`
+fn run_dot(input_path:&str, params:&Params, dot_path:&str, counts:bool) {
+    let dot = util::read_input(input_path)
+        .and_then(|lines| day11::Day11::parse_with(&lines, params))
+        .and_then(|day11| day11.to_dot(counts));
+    match dot {
+        Err(e) => {
+            eprintln!("Error: day 11: {}", e);
+            std::process::exit(error_exit_code(&e));
+        },
+        Ok(dot) => {
+            if let Err(e) = std::fs::write(dot_path, dot) {
+                eprintln!("Unable to write graph to {}: {}", dot_path, e);
+                std::process::exit(EXIT_USAGE);
+            }
+            println!("Device graph written to {}", dot_path);
+        }
+    }
+}
+
+/// Print how the buttons of each day 10 machine read from `input_path` are
+/// pressed to reach its lights and joltages
+fn run_explain(input_path:&str, params:&Params) {
+    match util::read_input(input_path).and_then(|lines| day10::Day10::parse_with(&lines, params)) {
+        Err(e) => {
+            eprintln!("Error: day 10: {}", e);
+            std::process::exit(error_exit_code(&e));
+        },
+        Ok(day10) => print!("{}", day10.explain())
+    }
+}
 
 fn main() {
-    
+
     let command = Command::new("adventofcode2025").max_term_width(80)
 					.about("Advent of Code 2025 solutions")
-                    .arg(Arg::new("day").help("Puzzle day to run").short('d').long("day").value_name("DAY").required(true))
-                    .arg(Arg::new("input").help("Path to input file").short('i').long("input").value_name("PATH"));
-	let args = command.get_matches();	
-	let day:usize;
-    match args.get_one::<String>("day") {
-        Some(day_arg) => {
-            match day_arg.parse::<usize>() {
-                Ok(n) => {
-                    day = n;
-                    if day < 1 || day > MAXDAY {
-                        println!("DAY must be in range 1 - {} ({} provided)", MAXDAY, n);
-                        std::process::exit(2);
+                    .arg(Arg::new("day").help("Puzzle day to run, or an inclusive range of days such as 3-7").short('d').long("day").value_name("DAY").required_unless_present("all"))
+                    .arg(Arg::new("all").help("Run all days with available inputs").short('a').long("all").action(ArgAction::SetTrue).conflicts_with("day"))
+                    .arg(Arg::new("input").help("Path to input file, or - to read from stdin").short('i').long("input").value_name("PATH"))
+                    .arg(Arg::new("param").help("Solver parameter such as connections=10 for day 8; may be repeated").short('p').long("param").value_name("KEY=VALUE").action(ArgAction::Append))
+                    .arg(Arg::new("check").help("Check the answers against the expected answers file").short('c').long("check").action(ArgAction::SetTrue).conflicts_with("bench"))
+                    .arg(Arg::new("answers").help("Path to expected answers file [default: answers.txt next to the input]").long("answers").value_name("PATH").requires("check"))
+                    .arg(Arg::new("format").help("Output format for results").short('f').long("format").value_name("FORMAT").value_parser(["text", "json", "csv"]).default_value("text").conflicts_with("bench"))
+                    .arg(Arg::new("bench").help("Benchmark the solution over N runs after a warm-up run").short('b').long("bench").value_name("N").value_parser(clap::builder::RangedU64ValueParser::<usize>::new().range(1..)))
+                    .arg(Arg::new("bench-save").help("Save benchmark results to a file").long("bench-save").value_name("PATH").requires("bench"))
+                    .arg(Arg::new("bench-compare").help("Compare benchmark results against a previously saved file and flag regressions").long("bench-compare").value_name("PATH").requires("bench"))
+                    .arg(Arg::new("dot").help("Write the day 11 device graph to a Graphviz DOT file instead of solving").long("dot").value_name("PATH").conflicts_with_all(["bench", "check", "format"]))
+                    .arg(Arg::new("dot-counts").help("Label each device in the DOT graph with its path counts from you and svr").long("dot-counts").action(ArgAction::SetTrue).requires("dot"))
+                    .arg(Arg::new("explain").help("Show the buttons pressed for each day 10 machine instead of solving").long("explain").action(ArgAction::SetTrue).conflicts_with_all(["bench", "check", "format", "dot"]));
+	let args = command.get_matches();
+	let days:Vec<usize>;
+    if args.get_flag("all") {
+        days = (1..=MAXDAY).collect();
+    }
+    else {
+        match args.get_one::<String>("day") {
+            Some(day_arg) => {
+                match parse_days(day_arg) {
+                    Ok(d) => days = d,
+                    Err(e) => {
+                        println!("{}", e);
+                        std::process::exit(EXIT_USAGE);
                     }
-                },
-                Err(_) => {
-                    println!("Could not parse DAY argument '{}' as an integer.", day_arg);
-                    std::process::exit(2);
                 }
+            },
+            None => {
+                println!("Day argument is required");
+                std::process::exit(EXIT_USAGE);
             }
-        },
-        None => {
-            println!("Day argument is required");
-            std::process::exit(2);
         }
     }
-    let input_path:String;
-    match args.get_one::<String>("input") {
-        Some(input_arg) => {
-            input_path = input_arg.clone();
-        },
-        None => {
-            input_path = format!("./data/day{}/input.txt", day);
+    let input_arg = args.get_one::<String>("input");
+    if days.len() > 1 && input_arg.is_some() {
+        println!("An input path can only be given when running a single day");
+        std::process::exit(EXIT_USAGE);
+    }
+    let check = args.get_flag("check");
+    let answers_arg = args.get_one::<String>("answers");
+    if days.len() > 1 && answers_arg.is_some() {
+        println!("An answers path can only be given when running a single day");
+        std::process::exit(EXIT_USAGE);
+    }
+    if input_arg.is_some_and(|i| i == "-") && check && answers_arg.is_none() {
+        println!("An answers path is required to check answers when reading input from stdin");
+        std::process::exit(EXIT_USAGE);
+    }
+    let param_args:Vec<String> = args.get_many::<String>("param").unwrap_or_default().cloned().collect();
+    let params = match Params::from_args(&param_args) {
+        Ok(p) => p,
+        Err(e) => {
+            println!("{}", e);
+            std::process::exit(EXIT_USAGE);
         }
+    };
+    if args.contains_id("dot") && days != vec![11] {
+        println!("A DOT graph can only be written for day 11");
+        std::process::exit(EXIT_USAGE);
+    }
+    if args.get_flag("explain") && days != vec![10] {
+        println!("Button presses can only be explained for day 10");
+        std::process::exit(EXIT_USAGE);
     }
-    println!(r#"
+    let format = match args.get_one::<String>("format").map(|f| f.as_str()) {
+        Some("json") => Format::Json,
+        Some("csv") => Format::Csv,
+        _ => Format::Text
+    };
+    if format == Format::Text {
+        println!(r#"
    ___     __              __         ___  _____        __      _  ___  ____
   / _ |___/ /  _____ ___  / /_  ___  / _/ / ___/__  ___/ /__   ( )|_  |/ __/
  / __ / _  / |/ / -_) _ \/ __/ / _ \/ _/ / /__/ _ \/ _  / -_)  |// __//__ \ 
 /_/ |_\_,_/|___/\__/_//_/\__/  \___/_/   \___/\___/\_,_/\__/    /____/____/ "#);
-    
-    println!("\nDay:\t{}\nInput:\t{}\n", day, input_path);
-            
-    match util::read_input(&input_path) {
-        Err(e) => {
-            println!("Error reading input: {}", e);
-            std::process::exit(2);
-        },
-        Ok(input_lines) => {
-            let start_instant = Instant::now();
-            match day {
-                1 => day1::run(&input_lines),
-                2 => day2::run(&input_lines),
-                3 => day3::run(&input_lines),
-                4 => day4::run(&input_lines),
-                5 => day5::run(&input_lines),
-                6 => day6::run(&input_lines),
-                7 => day7::run(&input_lines),
-                8 => day8::run(&input_lines),
-                9 => day9::run(&input_lines),
-                10 => day10::run(&input_lines),
-                11 => day11::run(&input_lines),
-                12 => day12::run(&input_lines),
-                _ => { println!("Day {} not matched", day)}
-            }
-            println!("\nTotal execution time: {:?}", start_instant.elapsed());
-        }
+    }
+
+    if let Some(dot_path) = args.get_one::<String>("dot") {
+        let input_path = match input_arg {
+            Some(input) => input.clone(),
+            None => runner::default_input_path(11)
+        };
+        run_dot(&input_path, &params, dot_path, args.get_flag("dot-counts"));
+    }
+    else if args.get_flag("explain") {
+        let input_path = match input_arg {
+            Some(input) => input.clone(),
+            None => runner::default_input_path(10)
+        };
+        run_explain(&input_path, &params);
+    }
+    else if let Some(runs) = args.get_one::<usize>("bench") {
+        run_bench(&days, input_arg, &params, *runs, args.get_one::<String>("bench-save"), args.get_one::<String>("bench-compare"));
+    }
+    else if days.len() == 1 {
+        let input_path = match input_arg {
+            Some(input) => input.clone(),
+            None => runner::default_input_path(days[0])
+        };
+        let answers_path = match answers_arg {
+            Some(answers) => Some(answers.clone()),
+            None if check => Some(runner::default_answers_path(&input_path)),
+            None => None
+        };
+        run_single(days[0], &input_path, &params, answers_path.as_deref(), format);
+    }
+    else {
+        run_many(&days, &params, check, format);
     }
 }
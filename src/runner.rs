@@ -0,0 +1,352 @@
+use crate::util;
+use crate::error::Error;
+use crate::solution::{self, Answer, Params};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Time taken by each phase of running a day's solution
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Timings {
+    /// Time taken to read the input, if it was read by the runner
+    pub io:Option<Duration>,
+    pub parse:Duration,
+    pub part1:Duration,
+    pub part2:Duration
+}
+impl Timings {
+    /// Total time taken to parse the input and solve both parts, excluding 
+    /// input reading
+    pub fn total(&self) -> Duration {
+        return self.parse + self.part1 + self.part2;
+    }
+}
+
+/// Outcome of checking a day's answers against the expected answers
+#[derive(Debug, Clone, PartialEq)]
+pub enum Check {
+    Pass,
+    /// Descriptions of each part that did not match
+    Mismatch(Vec<String>),
+    /// The expected answers could not be read
+    Missing(String)
+}
+impl std::fmt::Display for Check {
+    fn fmt(&self, f: &mut  std::fmt::Formatter) ->  std::fmt::Result {
+        match self {
+            Check::Pass => write!(f, "ok"),
+            Check::Mismatch(parts) => write!(f, "MISMATCH: {}", parts.join("; ")),
+            Check::Missing(reason) => write!(f, "unchecked: {}", reason)
+        }
+    }
+}
+
+/// Expected answers for a day. A part with no expected answer is not checked.
+pub struct ExpectedAnswers {
+    pub part1:Option<String>,
+    pub part2:Option<String>
+}
+impl ExpectedAnswers {
+    /// Compare the answers in `result` to the expected answers
+    pub fn check(&self, result:&DayResult) -> Check {
+        let mut mismatches = Vec::new();
+        let parts = [(1, &self.part1, &result.part1), (2, &self.part2, &result.part2)];
+        for (part, expected, actual) in parts {
+            if let Some(expected) = expected && *expected != actual.to_string() {
+                mismatches.push(format!("part {} expected {}, got {}", part, expected, actual));
+            }
+        }
+        if !mismatches.is_empty() {
+            return Check::Mismatch(mismatches);
+        }
+        return Check::Pass;
+    }
+}
+
+/// Outcome of running a single day's solution
+pub struct DayResult {
+    pub day:usize,
+    pub input_path:String,
+    pub part1:Answer,
+    pub part2:Answer,
+    pub timings:Timings,
+    /// Result of checking the answers, if they were checked
+    pub check:Option<Check>
+}
+
+/// A day that could not be run, with the reason why
+pub struct Skipped {
+    pub day:usize,
+    pub input_path:String,
+    pub reason:String
+}
+
+/// The default input file path for the given `day`
+pub fn default_input_path(day:usize) -> String {
+    return format!("./data/day{}/input.txt", day);
+}
+
+/// The default expected answers file path for the input at `input_path`. The
+/// answers file is `answers.txt` in the same directory as the input.
+pub fn default_answers_path(input_path:&str) -> String {
+    let dir = Path::new(input_path).parent().unwrap_or(Path::new(""));
+    return dir.join("answers.txt").to_string_lossy().to_string();
+}
+
+/// Read the expected answers file at `path`. The first line is the part 1 
+/// answer and the second line the part 2 answer. Missing or empty lines leave
+/// that part unchecked.
+pub fn read_answers(path:&str) -> Result<ExpectedAnswers, String> {
+    match fs::read_to_string(path) {
+        Err(_) => return Err(format!("Unable to open answers file at path {}", path)),
+        Ok(contents) => {
+            let lines:Vec<&str> = contents.lines().map(|l| l.trim()).collect();
+            let answer = |i:usize| {
+                if i < lines.len() && !lines[i].is_empty() {
+                    return Some(lines[i].to_string());
+                }
+                return None;
+            };
+            return Ok(ExpectedAnswers { part1:answer(0), part2:answer(1) });
+        }
+    }
+}
+
+/// Parse and solve both parts of the given `day` with solver `params` for the
+/// input `lines` read from `input_path`. Returns the first error from parsing or either part, or
+/// an error if no solution exists for the day.
+pub fn run_day(day:usize, input_path:&str, lines:&Vec<String>, params:&Params) -> Result<DayResult, Error> {
+    let parser = match solution::get(day) {
+        Some(parser) => parser,
+        None => return Err(Error::Input(format!("no solution for day {}", day)))
+    };
+    let mut timings = Timings::default();
+    let mut start_instant = Instant::now();
+    let solution = parser(lines, params)?;
+    timings.parse = start_instant.elapsed();
+    start_instant = Instant::now();
+    let part1 = solution.part1()?;
+    timings.part1 = start_instant.elapsed();
+    start_instant = Instant::now();
+    let part2 = solution.part2()?;
+    timings.part2 = start_instant.elapsed();
+    return Ok(DayResult { day, input_path:input_path.to_string(), part1, part2, timings, check:None });
+}
+
+/// Read the input file at `input_path` (or stdin if `-`) and run the given 
+/// `day` on it, recording the time taken to read the input along with the 
+/// solution timings.
+pub fn run_day_input(day:usize, input_path:&str, params:&Params) -> Result<DayResult, Error> {
+    let start_instant = Instant::now();
+    let lines = util::read_input(input_path)?;
+    let io = start_instant.elapsed();
+    let mut result = run_day(day, input_path, &lines, params)?;
+    result.timings.io = Some(io);
+    return Ok(result);
+}
+
+/// Format an optional duration for display
+fn format_duration(duration:Option<Duration>) -> String {
+    match duration {
+        Some(d) => return format!("{:?}", d),
+        None => return "-".to_string()
+    }
+}
+
+/// Format the given `results` as a table of answers and timings. Days listed
+/// in `skipped` are included with the reason they were not run. If any 
+/// answers were checked, the check results are included.
+pub fn summary_table(results:&Vec<DayResult>, skipped:&Vec<Skipped>) -> String {
+    let mut headers = vec!["Day", "Part 1", "Part 2", "I/O", "Parse", "Time 1", "Time 2", "Total"];
+    let checked = results.iter().any(|r| r.check.is_some());
+    if checked {
+        headers.push("Check");
+    }
+    let mut rows:Vec<(usize, Vec<String>)> = Vec::new();
+    for result in results {
+        let t = &result.timings;
+        let mut row = vec![result.day.to_string(), result.part1.to_string(), result.part2.to_string(), 
+            format_duration(t.io), format!("{:?}", t.parse), format!("{:?}", t.part1), format!("{:?}", t.part2), format!("{:?}", t.total())];
+        if checked {
+            match &result.check {
+                Some(check) => row.push(check.to_string()),
+                None => row.push("-".to_string())
+            }
+        }
+        rows.push((result.day, row));
+    }
+    for skip in skipped {
+        rows.push((skip.day, vec![skip.day.to_string(), format!("skipped: {}", skip.reason)]));
+    }
+    rows.sort_by_key(|a| a.0);
+    // Sum each timing column
+    let io:Duration = results.iter().filter_map(|r| r.timings.io).sum();
+    let parse:Duration = results.iter().map(|r| r.timings.parse).sum();
+    let part1:Duration = results.iter().map(|r| r.timings.part1).sum();
+    let part2:Duration = results.iter().map(|r| r.timings.part2).sum();
+    let total:Duration = results.iter().map(|r| r.timings.total()).sum();
+    let mut totals = vec![String::new(), String::new(), "Total".to_string(), format!("{:?}", io), format!("{:?}", parse), format!("{:?}", part1), format!("{:?}", part2), format!("{:?}", total)];
+    if checked {
+        let failures = results.iter().filter(|r| matches!(r.check, Some(Check::Mismatch(_)))).count();
+        totals.push(format!("{} mismatched", failures));
+    }
+    let mut rows:Vec<Vec<String>> = rows.into_iter().map(|(_, row)| row).collect();
+    rows.push(totals);
+    let headers:Vec<String> = headers.iter().map(|h| h.to_string()).collect();
+    return format_table(&headers, &rows);
+}
+
+/// Quote `s` as a JSON string
+fn json_string(s:&str) -> String {
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c)
+        }
+    }
+    quoted.push('"');
+    return quoted;
+}
+
+/// Format an answer as a JSON string, or null if there is no answer
+fn json_answer(answer:&Answer) -> String {
+    match answer {
+        Answer::None => return "null".to_string(),
+        _ => return json_string(&answer.to_string())
+    }
+}
+
+/// Format the given `results` and `skipped` days as a JSON array of objects,
+/// one per day in day order. Answers are given as strings (or null for no 
+/// answer) and timings in nanoseconds. Skipped days have null answers and 
+/// timings and an `error` describing why they were not run.
+pub fn json_report(results:&Vec<DayResult>, skipped:&Vec<Skipped>) -> String {
+    let mut entries:Vec<(usize, String)> = Vec::new();
+    for result in results {
+        let t = &result.timings;
+        let io = match t.io {
+            Some(d) => d.as_nanos().to_string(),
+            None => "null".to_string()
+        };
+        let check = match &result.check {
+            Some(c) => json_string(&c.to_string()),
+            None => "null".to_string()
+        };
+        entries.push((result.day, format!("{{\"day\":{},\"input\":{},\"part1\":{},\"part2\":{},\"timings\":{{\"io_ns\":{},\"parse_ns\":{},\"part1_ns\":{},\"part2_ns\":{},\"total_ns\":{}}},\"check\":{},\"error\":null}}",
+            result.day, json_string(&result.input_path), json_answer(&result.part1), json_answer(&result.part2),
+            io, t.parse.as_nanos(), t.part1.as_nanos(), t.part2.as_nanos(), t.total().as_nanos(), check)));
+    }
+    for skip in skipped {
+        entries.push((skip.day, format!("{{\"day\":{},\"input\":{},\"part1\":null,\"part2\":null,\"timings\":null,\"check\":null,\"error\":{}}}",
+            skip.day, json_string(&skip.input_path), json_string(&skip.reason))));
+    }
+    entries.sort_by_key(|a| a.0);
+    let lines:Vec<String> = entries.into_iter().map(|(_, entry)| format!("  {}", entry)).collect();
+    return format!("[\n{}\n]\n", lines.join(",\n"));
+}
+
+/// Quote `s` as a CSV field if it contains a separator, quote or line break
+fn csv_field(s:&str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        return format!("\"{}\"", s.replace("\"", "\"\""));
+    }
+    return s.to_string();
+}
+
+/// Format the given `results` and `skipped` days as CSV with a header row, 
+/// one row per day in day order. Timings are in nanoseconds. Fields that don't
+/// apply to a row are left empty.
+pub fn csv_report(results:&Vec<DayResult>, skipped:&Vec<Skipped>) -> String {
+    let mut rows:Vec<(usize, Vec<String>)> = Vec::new();
+    for result in results {
+        let t = &result.timings;
+        let answer = |a:&Answer| if *a == Answer::None { String::new() } else { a.to_string() };
+        rows.push((result.day, vec![result.day.to_string(), result.input_path.clone(), answer(&result.part1), answer(&result.part2),
+            t.io.map(|d| d.as_nanos().to_string()).unwrap_or_default(), t.parse.as_nanos().to_string(), 
+            t.part1.as_nanos().to_string(), t.part2.as_nanos().to_string(), t.total().as_nanos().to_string(),
+            result.check.as_ref().map(|c| c.to_string()).unwrap_or_default(), String::new()]));
+    }
+    for skip in skipped {
+        let mut row = vec![String::new(); 11];
+        row[0] = skip.day.to_string();
+        row[1] = skip.input_path.clone();
+        row[10] = skip.reason.clone();
+        rows.push((skip.day, row));
+    }
+    rows.sort_by_key(|a| a.0);
+    let mut csv = String::from("day,input,part1,part2,io_ns,parse_ns,part1_ns,part2_ns,total_ns,check,error\n");
+    for (_, row) in rows {
+        let fields:Vec<String> = row.iter().map(|f| csv_field(f)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    return csv;
+}
+
+/// Format a table with the given column `headers` and `rows`. Column widths 
+/// fit the widest entry. Rows shorter than the headers (such as a skip reason)
+/// are not used for the widths and are allowed to overflow the columns.
+pub fn format_table(headers:&Vec<String>, rows:&Vec<Vec<String>>) -> String {
+    let mut widths:Vec<usize> = headers.iter().map(|h| h.len()).collect();
+    for row in rows.iter() {
+        if row.len() != headers.len() {
+            continue;
+        }
+        for i in 0..row.len() {
+            widths[i] = widths[i].max(row[i].len());
+        }
+    }
+    let mut table = String::new();
+    table.push_str(&format_row(headers, &widths));
+    table.push_str(&format_row(&widths.iter().map(|w| "-".repeat(*w)).collect(), &widths));
+    for row in rows.iter() {
+        table.push_str(&format_row(row, &widths));
+    }
+    return table;
+}
+
+/// Format a table row, with the first column right-aligned and the others
+/// left-aligned
+fn format_row(cells:&Vec<String>, widths:&Vec<usize>) -> String {
+    let mut line = String::new();
+    for i in 0..cells.len() {
+        if i == 0 {
+            line.push_str(&format!("{:>width$}", cells[i], width = widths[i]));
+        }
+        else {
+            line.push_str(&format!("  {:<width$}", cells[i], width = widths[i]));
+        }
+    }
+    line.truncate(line.trim_end().len());
+    line.push('\n');
+    return line;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_and_check_example() {
+        let lines = util::read_lines(include_str!("../testdata/day1.txt").as_bytes()).unwrap();
+        let result = run_day(1, "-", &lines, &Params::new()).unwrap();
+        assert_eq!((result.part1.clone(), result.part2.clone()), (Answer::Int(3), Answer::Int(6)));
+        let expected = ExpectedAnswers { part1:Some("3".to_string()), part2:Some("7".to_string()) };
+        assert_eq!(expected.check(&result), Check::Mismatch(vec!["part 2 expected 7, got 6".to_string()]));
+        let expected = ExpectedAnswers { part1:Some("3".to_string()), part2:None };
+        assert_eq!(expected.check(&result), Check::Pass);
+        assert!(run_day(1, "-", &vec!["X1".to_string()], &Params::new()).is_err());
+    }
+    #[test]
+    fn escaping() {
+        assert_eq!(json_string("a \"b\"\n"), "\"a \\\"b\\\"\\n\"");
+        assert_eq!(csv_field("a,\"b\""), "\"a,\"\"b\"\"\"");
+        assert_eq!(csv_field("plain"), "plain");
+    }
+}
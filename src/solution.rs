@@ -0,0 +1,157 @@
+use crate::error::Error;
+use crate::util::BigInt;
+use std::collections::HashMap;
+use std::str::FromStr;
+use crate::{day1, day2, day3, day4, day5, day6, day7, day8, day9, day10, day11, day12};
+
+/// A puzzle answer returned by a `Solution` part
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Int(i128),
+    /// An integer answer too large for `Int`
+    Big(BigInt),
+    Text(String),
+    /// The puzzle has no answer for this part (e.g. day 12 part 2)
+    None
+}
+impl std::fmt::Display for Answer {
+    fn fmt(&self, f: &mut  std::fmt::Formatter) ->  std::fmt::Result {
+        match self {
+            Answer::Int(n) => write!(f, "{}", n),
+            Answer::Big(n) => write!(f, "{}", n),
+            Answer::Text(s) => write!(f, "{}", s),
+            Answer::None => write!(f, "-")
+        }
+    }
+}
+impl From<usize> for Answer {
+    fn from(n:usize) -> Answer {
+        return Answer::Int(n as i128);
+    }
+}
+impl From<isize> for Answer {
+    fn from(n:isize) -> Answer {
+        return Answer::Int(n as i128);
+    }
+}
+impl From<i64> for Answer {
+    fn from(n:i64) -> Answer {
+        return Answer::Int(n as i128);
+    }
+}
+impl From<BigInt> for Answer {
+    /// An `Int` answer if `n` fits in one, otherwise a `Big` answer
+    fn from(n:BigInt) -> Answer {
+        match n.to_i128() {
+            Some(small) => return Answer::Int(small),
+            None => return Answer::Big(n)
+        }
+    }
+}
+impl From<i128> for Answer {
+    fn from(n:i128) -> Answer {
+        return Answer::Int(n);
+    }
+}
+impl From<String> for Answer {
+    fn from(s:String) -> Answer {
+        return Answer::Text(s);
+    }
+}
+
+/// Named solver parameters given as `key=value` pairs, for solutions with
+/// settings beyond the puzzle input (e.g. the day 8 connection count)
+#[derive(Debug, Clone, Default)]
+pub struct Params {
+    values:HashMap<String, String>
+}
+impl Params {
+    pub fn new() -> Params {
+        return Params::default();
+    }
+    /// Parse parameters from `key=value` strings
+    pub fn from_args(args:&Vec<String>) -> Result<Params, Error> {
+        let mut params = Params::new();
+        for arg in args {
+            match arg.split_once("=") {
+                Some((key, value)) if !key.trim().is_empty() => params.insert(key.trim(), value.trim()),
+                _ => return Err(Error::Param(format!("expected key=value, found '{}'", arg)))
+            }
+        }
+        return Ok(params);
+    }
+    /// Set the parameter `key` to `value`
+    pub fn insert(&mut self, key:&str, value:&str) {
+        self.values.insert(key.to_string(), value.to_string());
+    }
+    /// Get the parameter `key` parsed as a `T`, or `default` if it isn't set
+    pub fn get<T: FromStr>(&self, key:&str, default:T) -> Result<T, Error> {
+        match self.values.get(key) {
+            None => return Ok(default),
+            Some(value) => match value.parse::<T>() {
+                Ok(x) => return Ok(x),
+                Err(_) => return Err(Error::Param(format!("could not parse {}={}", key, value)))
+            }
+        }
+    }
+}
+
+/// Add a suggestion to set the `bigint` parameter to an overflow error from a
+/// solution that supports it
+pub fn suggest_bigint(e:Error) -> Error {
+    match e {
+        Error::Overflow(message) => return Error::Overflow(format!("{}; set the bigint parameter for an exact answer", message)),
+        e => return e
+    }
+}
+
+/// Common interface for each day's puzzle solver. The input is parsed once by
+/// `parse` and both parts are computed from the parsed state.
+pub trait Solution {
+    /// Parse the puzzle input `lines`
+    fn parse(lines:&Vec<String>) -> Result<Self, Error> where Self: Sized;
+    /// Parse the puzzle input `lines` with solver `params`. Solutions without
+    /// parameters ignore them and use `parse`.
+    fn parse_with(lines:&Vec<String>, _params:&Params) -> Result<Self, Error> where Self: Sized {
+        return Self::parse(lines);
+    }
+    /// Compute the part 1 answer
+    fn part1(&self) -> Result<Answer, Error>;
+    /// Compute the part 2 answer
+    fn part2(&self) -> Result<Answer, Error>;
+}
+
+/// Constructor for a boxed `Solution` from the puzzle input lines and solver
+/// parameters
+pub type Parser = fn(&Vec<String>, &Params) -> Result<Box<dyn Solution>, Error>;
+
+fn parser<T: Solution + 'static>(lines:&Vec<String>, params:&Params) -> Result<Box<dyn Solution>, Error> {
+    return Ok(Box::new(T::parse_with(lines, params)?));
+}
+
+/// Registry of solutions, indexed by puzzle day - 1
+pub const SOLUTIONS:[Parser;12] = [
+    parser::<day1::Day1>,
+    parser::<day2::Day2>,
+    parser::<day3::Day3>,
+    parser::<day4::Day4>,
+    parser::<day5::Day5>,
+    parser::<day6::Day6>,
+    parser::<day7::Day7>,
+    parser::<day8::Day8>,
+    parser::<day9::Day9>,
+    parser::<day10::Day10>,
+    parser::<day11::Day11>,
+    parser::<day12::Day12>,
+];
+
+/// Number of puzzle days with a registered solution
+pub const MAXDAY:usize = SOLUTIONS.len();
+
+/// Get the parser for the given puzzle `day` (1-based), if one exists
+pub fn get(day:usize) -> Option<Parser> {
+    if !(1..=MAXDAY).contains(&day) {
+        return None;
+    }
+    return Some(SOLUTIONS[day - 1]);
+}
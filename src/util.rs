@@ -4,6 +4,27 @@ use std::hash::Hash;
 use std::fs::File;
 use std::io::{BufReader, BufRead};
 use regex::Regex;
+use crate::error::Error;
+
+pub mod bigint;
+pub mod bitmatrix;
+pub mod graph;
+pub mod grid;
+pub mod kdtree;
+pub mod lp;
+pub mod matrix;
+pub mod rational;
+pub mod search;
+pub mod unionfind;
+pub use bigint::BigInt;
+pub use bitmatrix::{BitMatrix, BitVec, Gf2Solution, MinCostSolution};
+pub use graph::DiGraph;
+pub use grid::Grid;
+pub use kdtree::{KdTree, Point};
+pub use lp::{LinearProgram, LpSolution, Relation};
+pub use matrix::{Field, Gf2, Matrix, Scalar, SolutionSet};
+pub use rational::{Integer, Rational};
+pub use unionfind::UnionFind;
 
 // Utility types
 /// Generic defaultdict equivalent with keys of type `T` and values of type `U` 
@@ -23,6 +44,9 @@ impl<T: Eq + Hash + Copy,U: Copy> DefaultHashMap<T,U> {
     pub fn len(&self) -> usize {
         return self.map.len();
     }
+    pub fn is_empty(&self) -> bool {
+        return self.map.is_empty();
+    }
     pub fn keys(&self) -> Keys<'_, T, U> {
         return self.map.keys();
     }
@@ -60,7 +84,7 @@ pub struct Vec2 {
 }
 impl Vec2 {
     pub fn new(x:isize, y:isize) -> Vec2 {
-        return Vec2{x:x, y:y};
+        return Vec2{x, y};
     }
     pub fn newu(x:usize, y:usize) -> Vec2 {
         return Vec2{x:x as isize, y:y as isize};
@@ -70,6 +94,10 @@ impl Vec2 {
     pub fn in_bounds(self, dim_x:usize, dim_y:usize) -> bool {
         return self.x >= 0 && self.x < dim_x as isize && self.y >= 0 && self.y < dim_y as isize;
     }
+    /// Manhattan distance to `other`
+    pub fn manhattan(self, other:Vec2) -> isize {
+        return (self.x - other.x).abs() + (self.y - other.y).abs();
+    }
 }
 impl std::fmt::Display for Vec2 {
     fn fmt(&self, f: &mut  std::fmt::Formatter) ->  std::fmt::Result {
@@ -97,7 +125,7 @@ pub struct Vec3 {
 }
 impl Vec3 {
     pub fn new(x:isize, y:isize, z:isize) -> Vec3 {
-        return Vec3{x:x, y:y, z:z};
+        return Vec3{x, y, z};
     }
     pub fn newu(x:usize, y:usize, z:usize) -> Vec3 {
         return Vec3{x:x as isize, y:y as isize, z:z as isize};
@@ -136,212 +164,28 @@ impl std::ops::Mul<isize> for Vec3 {
     }
 }
 
-/// Basic rational type
-#[derive(Copy, Clone)]
-pub struct Rational {
-    pub num:isize,
-    pub denom:isize
-}
-impl Rational {
-    pub fn new(numerator:isize, denominator:isize) -> Rational {
-        assert!(denominator != 0, "{}/{} has denominator zero", numerator, denominator);
-        if numerator == 0 {
-            return Rational {num:0, denom:1};
-        }
-        // Divide out gcd between numerator and denominator from both
-        let gcd = gcd(numerator as i128, denominator as i128) as isize;
-        let num = numerator / gcd;
-        let denom = denominator / gcd;
-        // have denominator positive
-        let neg = if denom < 0 {-1} else {1};
-        return Rational { num: neg * num, denom: neg * denom };
-    }
-    pub fn new_int(numerator:isize) -> Rational {
-        return Rational { num: numerator, denom: 1 };
-    }
-}
-impl std::fmt::Display for Rational {
-    fn fmt(&self, f: &mut  std::fmt::Formatter) ->  std::fmt::Result {
-        if self.denom == 1 {
-            write!(f,"{}", self.num)
-        } else {
-            write!(f,"{}/{}", self.num, self.denom)
+// General helper functions
+// File input
+/// Read all lines from `reader`
+pub fn read_lines<R: BufRead>(reader:R) -> Result<Vec<String>, Error> {
+    let mut vec:Vec<String> = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        match line {
+            Ok(line) => vec.push(line),
+            Err(e) => return Err(Error::Io(format!("Unable to read line {}: {}", index + 1, e)))
         }
     }
+    return Ok(vec);
 }
-impl std::ops::Neg for Rational {
-    type Output = Rational;
-    fn neg(self) -> Rational {
-        return Rational::new(-self.num, self.denom);
-    }
-}
-impl std::ops::Add<Rational> for Rational {
-    type Output = Rational;
-    fn add(self, rhs:Rational) -> Rational {
-        let numerator = (self.num * rhs.denom) + (rhs.num * self.denom);
-        let denominator = self.denom * rhs.denom;
-        return Rational::new(numerator, denominator);
-    }
-}
-impl std::ops::Add<isize> for Rational {
-    type Output = Rational;
-    fn add(self, rhs:isize) -> Rational {
-        let numerator = self.num + (rhs * self.denom);
-        let denominator = self.denom;
-        return Rational::new(numerator, denominator);
-    }
-}
-impl std::ops::Sub<Rational> for Rational {
-    type Output = Rational;
-    fn sub(self, rhs:Rational) -> Rational {
-        let numerator = (self.num * rhs.denom) - (rhs.num * self.denom);
-        let denominator = self.denom * rhs.denom;
-        return Rational::new(numerator, denominator);
-    }
-}
-impl std::ops::Sub<isize> for Rational {
-    type Output = Rational;
-    fn sub(self, rhs:isize) -> Rational {
-        let numerator = self.num - (rhs * self.denom);
-        let denominator = self.denom;
-        return Rational::new(numerator, denominator);
-    }
-}
-impl std::ops::Mul<Rational> for Rational {
-    type Output = Rational;
-    fn mul(self, rhs:Rational) -> Rational {
-        let numerator = self.num * rhs.num;
-        let denominator = self.denom * rhs.denom;
-        return Rational::new(numerator, denominator);
-    }
-}
-impl std::ops::Mul<isize> for Rational {
-    type Output = Rational;
-    fn mul(self, rhs:isize) -> Rational {
-        let numerator = self.num * rhs;
-        let denominator = self.denom;
-        return Rational::new(numerator, denominator);
-    }
-}
-impl std::ops::Div<Rational> for Rational {
-    type Output = Rational;
-    fn div(self, rhs:Rational) -> Rational {
-        assert!(rhs != 0,"Division of {} by zero", self);
-        let numerator = self.num * rhs.denom;
-        let denominator = self.denom * rhs.num;
-        return Rational::new(numerator, denominator);
-    }
-}
-impl std::ops::Div<isize> for Rational {
-    type Output = Rational;
-    fn div(self, rhs:isize) -> Rational {
-        assert!(rhs != 0,"Division of {} by zero", self);
-        let numerator = self.num;
-        let denominator = self.denom * rhs;
-        return Rational::new(numerator, denominator);
-    }
-}
-impl std::ops::AddAssign<Rational> for Rational {
-    fn add_assign(&mut self, rhs: Rational) {
-        let temp = *self + rhs;
-        self.num = temp.num;
-        self.denom = temp.denom;
-    }
-}
-impl std::ops::AddAssign<isize> for Rational {
-    fn add_assign(&mut self, rhs: isize) {
-        let temp = *self + rhs;
-        self.num = temp.num;
-        self.denom = temp.denom;
-    }
-}
-impl std::ops::SubAssign<Rational> for Rational {
-    fn sub_assign(&mut self, rhs: Rational) {
-        let temp = *self - rhs;
-        *self = temp;
-    }
-}
-impl std::ops::SubAssign<isize> for Rational {
-    fn sub_assign(&mut self, rhs: isize) {
-        let temp = *self - rhs;
-        *self = temp;
-    }
-}
-impl std::ops::MulAssign<Rational> for Rational {
-    fn mul_assign(&mut self, rhs: Rational) {
-        let temp = *self * rhs;
-        *self = temp;
+/// Read an input file into lines. A `file_path` of `-` reads from stdin.
+pub fn read_input(file_path:&str) -> Result<Vec<String>, Error>{
+    if file_path == "-" {
+        return read_lines(std::io::stdin().lock());
     }
-}
-impl std::ops::MulAssign<isize> for Rational {
-    fn mul_assign(&mut self, rhs: isize) {
-        let temp = *self * rhs;
-        *self = temp;
-    }
-}
-impl std::ops::DivAssign<Rational> for Rational {
-    fn div_assign(&mut self, rhs: Rational) {
-        let temp = *self / rhs;
-        *self = temp;
-    }
-}
-impl std::ops::DivAssign<isize> for Rational {
-    fn div_assign(&mut self, rhs: isize) {
-        let temp = *self / rhs;
-        *self = temp;
-    }
-}
-impl std::cmp::Ord for Rational {
-    fn cmp(&self, other:&Rational) -> std::cmp::Ordering {
-        let numerator = self.num * other.denom;
-        let denominator = self.denom * other.num;
-        return numerator.cmp(&denominator);
-    }
-}
-impl Eq for Rational {}
-impl std::cmp::PartialOrd<Rational> for Rational {
-    fn partial_cmp(&self, other:&Rational) -> Option<std::cmp::Ordering> {
-        let self_numerator = self.num * other.denom;
-        let other_numerator = other.num * self.denom;
-        return Some(self_numerator.cmp(&other_numerator));
-    }
-}
-impl std::cmp::PartialEq<Rational> for Rational {
-    fn eq(&self, other:&Rational) -> bool {
-        let self_numerator = self.num * other.denom;
-        let other_numerator = other.num * self.denom;
-        return self_numerator.eq(&other_numerator);
-    }
-}
-impl std::cmp::PartialOrd<isize> for Rational {
-    fn partial_cmp(&self, other:&isize) -> Option<std::cmp::Ordering> {
-        let self_numerator = self.num;
-        let other_numerator = other * self.denom;
-        return Some(self_numerator.cmp(&other_numerator));
-    }
-}
-impl std::cmp::PartialEq<isize> for Rational {
-    fn eq(&self, other:&isize) -> bool {
-        let self_numerator = self.num;
-        let other_numerator = other* self.denom;
-        return self_numerator.eq(&other_numerator);
-    }
-}
-
-// General helper functions
-// File input
-/// Read an input file into lines
-pub fn read_input(file_path:&str) -> Result<Vec<String>, String>{
     match File::open(file_path) {
-        Err(_) => Err(format!("Unable to open file at path {}", file_path)),
+        Err(_) => Err(Error::Io(format!("Unable to open file at path {}", file_path))),
         Ok(file) => {
-            let mut vec:Vec<String> = Vec::new();
-            let reader = BufReader::new(file);
-            for (_index, line) in reader.lines().enumerate() {
-                let line = line.unwrap();
-                vec.push(line);
-            }
-	        return Ok(vec);
+            return read_lines(BufReader::new(file));
         }
     }
 }
@@ -351,7 +195,7 @@ pub fn sections(lines:&Vec<String>) -> Vec<Vec<String>> {
     sections.push(Vec::new());
     let mut section_count = 1;
     for i in 0..lines.len() {
-        if lines[i].len() == 0 {
+        if lines[i].is_empty() {
             sections.push(Vec::new());
             section_count += 1;
         }
@@ -361,15 +205,28 @@ pub fn sections(lines:&Vec<String>) -> Vec<Vec<String>> {
     }
     return sections;
 }
+/// Break input lines into sections on empty lines, along with the 0-based 
+/// index of the first line of each section in `lines`
+pub fn sections_indexed(lines:&Vec<String>) -> Vec<(usize, Vec<String>)> {
+    let mut sections:Vec<(usize, Vec<String>)> = vec![(0, Vec::new())];
+    for i in 0..lines.len() {
+        if lines[i].is_empty() {
+            sections.push((i + 1, Vec::new()));
+        }
+        else {
+            sections.last_mut().unwrap().1.push(lines[i].clone());
+        }
+    }
+    return sections;
+}
 /// Extract all base 10 integers in a string
 pub fn ints_in_string(string:&String) -> Vec<isize> {
     let re = Regex::new(r"-?\d+\.?\d*").unwrap();
     let string_matches: Vec<&str> = re.find_iter(string).map(|m| m.as_str()).collect();
     let mut result:Vec<isize> = Vec::new();
     for s in string_matches {
-        match s.parse::<isize>() {
-            Ok(x) => result.push(x),
-            _ => {}
+        if let Ok(x) = s.parse::<isize>() {
+            result.push(x);
         }
     }
     return result;
@@ -384,9 +241,8 @@ pub fn ints_in_strings(strings:&Vec<String>) -> Vec<Vec<isize>> {
         let string_matches: Vec<&str> = re.find_iter(string).map(|m| m.as_str()).collect();
         let mut ints:Vec<isize> = Vec::new();
         for s in string_matches {
-            match s.parse::<isize>() {
-                Ok(x) => ints.push(x),
-                _ => {}
+            if let Ok(x) = s.parse::<isize>() {
+                ints.push(x);
             }
         }
         result.push(ints);
@@ -399,9 +255,8 @@ pub fn floats_in_string(string:&String) -> Vec<f64> {
     let string_matches: Vec<&str> = re.find_iter(string).map(|m| m.as_str()).collect();
     let mut result:Vec<f64> = Vec::new();
     for s in string_matches {
-        match s.parse::<f64>() {
-            Ok(x) => result.push(x),
-            _ => {}
+        if let Ok(x) = s.parse::<f64>() {
+            result.push(x);
         }
     }
     return result;
@@ -416,9 +271,8 @@ pub fn floats_in_strings(strings:&Vec<String>) -> Vec<Vec<f64>> {
         let string_matches: Vec<&str> = re.find_iter(string).map(|m| m.as_str()).collect();
         let mut floats:Vec<f64> = Vec::new();
         for s in string_matches {
-            match s.parse::<f64>() {
-                Ok(x) => floats.push(x),
-                _ => {}
+            if let Ok(x) = s.parse::<f64>() {
+                floats.push(x);
             }
         }
         result.push(floats);
@@ -447,18 +301,18 @@ pub fn print_grid(grid:&Vec<Vec<char>>) {
     }
 }
 /// Reads the grid as a `DefaultHashMap`, plus the grid width and height
-pub fn read_grid_map(lines:&Vec<String>, default_char:char) -> Result<(DefaultHashMap<Vec2, char>, usize, usize), String> {
+pub fn read_grid_map(lines:&Vec<String>, default_char:char) -> Result<(DefaultHashMap<Vec2, char>, usize, usize), Error> {
     let mut map = DefaultHashMap::<Vec2, char>::new(default_char);
-    if lines.len() == 0 {
-        return Err("lines are empty".to_string());
+    if lines.is_empty() {
+        return Err(Error::Input("lines are empty".to_string()));
     }
-    let width = lines[0].len();
+    let width = lines[0].chars().count();
     let height = lines.len();
     for y in 0..lines.len() {
-        if lines[y].len() != width {
-            return Err(format!("Irregular grid: expecting width {} at line {}, found {}", width, y, lines[y].len()));
-        }
         let line_chars:Vec<char> = lines[y].chars().collect();
+        if line_chars.len() != width {
+            return Err(Error::parse_at(y, line_chars.len().min(width), &format!("Irregular grid: expecting width {}, found {}", width, line_chars.len())));
+        }
         for x in 0..width {
             if line_chars[x] != default_char {
                 map.insert(Vec2::newu(x,y), line_chars[x]);
@@ -554,14 +408,11 @@ pub fn base_n_digits(x:i128, n:usize, required_len:Option<usize>) -> Vec<i128> {
         x_temp -= digit;
         x_temp /= n as i128;
     }
-    match required_len {
-        Some(len) => {
-            // prepend with zeroes
-            while digits.len() < len {
-                digits.insert(0, 0);
-            }
-        },
-        None => {}
+    if let Some(len) = required_len {
+        // prepend with zeroes
+        while digits.len() < len {
+            digits.insert(0, 0);
+        }
     }
     return digits;
 }
@@ -668,3 +519,49 @@ pub fn crt(congruences:&Vec<(i128, i128)>) -> Option<i128> {
     }
     return Some(sum % product);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(input:&str) -> Vec<String> {
+        return read_lines(input.as_bytes()).unwrap();
+    }
+    #[test]
+    fn sections_split_on_blank_lines() {
+        let input = lines("a\nb\n\nc");
+        assert_eq!(sections(&input), vec![vec!["a".to_string(), "b".to_string()], vec!["c".to_string()]]);
+        assert_eq!(sections_indexed(&input), vec![(0, vec!["a".to_string(), "b".to_string()]), (3, vec!["c".to_string()])]);
+    }
+    #[test]
+    fn ints_in_strings_extracts_signed_ints() {
+        assert_eq!(ints_in_string(&"p=-3,14 v=15".to_string()), vec![-3, 14, 15]);
+        assert_eq!(ints_in_strings(&lines("1,2\nx\n-4")), vec![vec![1, 2], vec![], vec![-4]]);
+    }
+    #[test]
+    fn grid_map() {
+        let (map, width, height) = read_grid_map(&lines(".#\n#."), '.').unwrap();
+        assert_eq!((width, height), (2, 2));
+        assert_eq!(*map.get(&Vec2::new(1, 0)), '#');
+        assert_eq!(*map.get(&Vec2::new(1, 1)), '.');
+        assert!(matches!(read_grid_map(&lines(".#\n#"), '.'), Err(Error::Parse { line:2, .. })));
+        assert!(read_grid_map(&Vec::new(), '.').is_err());
+    }
+    #[test]
+    fn vector_ops() {
+        assert_eq!(Vec2::new(1, 2) + Vec2::new(3, -4), Vec2::new(4, -2));
+        assert_eq!(Vec3::new(1, 2, 3) - Vec3::new(1, 0, 1), Vec3::new(0, 2, 2));
+        assert_eq!(Vec3::new(1, 2, 2).len_squared(), 9);
+    }
+    #[test]
+    fn number_theory() {
+        assert_eq!(abs_mod(-7, 3), 2);
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd_list(&vec![12, 18, 8]), 2);
+        assert_eq!(lcm(4, 6), 12);
+        assert_eq!(mod_inv(3, 7), Some(5));
+        assert_eq!(crt(&vec![(3, 2), (5, 3), (7, 2)]), Some(23));
+        assert_eq!(eratosthenes(20), vec![2, 3, 5, 7, 11, 13, 17, 19]);
+        assert_eq!(base_n_digits(6, 2, Some(4)), vec![0, 1, 1, 0]);
+    }
+}
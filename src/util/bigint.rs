@@ -0,0 +1,461 @@
+use crate::error::Error;
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+/// Arbitrary-precision signed integer, stored as a sign and a magnitude of
+/// base 2^32 limbs, least significant first
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative:bool,
+    /// Limbs of the absolute value, with no trailing zero limbs, so zero has
+    /// no limbs
+    magnitude:Vec<u32>
+}
+
+/// Remove trailing zero limbs
+fn trim(magnitude:&mut Vec<u32>) {
+    while magnitude.last() == Some(&0) {
+        magnitude.pop();
+    }
+}
+fn cmp_magnitude(a:&[u32], b:&[u32]) -> Ordering {
+    if a.len() != b.len() {
+        return a.len().cmp(&b.len());
+    }
+    for i in (0..a.len()).rev() {
+        if a[i] != b[i] {
+            return a[i].cmp(&b[i]);
+        }
+    }
+    return Ordering::Equal;
+}
+fn add_magnitude(a:&[u32], b:&[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut result = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for i in 0..long.len() {
+        let sum = long[i] as u64 + if i < short.len() { short[i] as u64 } else { 0 } + carry;
+        result.push(sum as u32);
+        carry = sum >> 32;
+    }
+    if carry > 0 {
+        result.push(carry as u32);
+    }
+    return result;
+}
+/// `a` - `b` where `a` >= `b`
+fn sub_magnitude(a:&[u32], b:&[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for i in 0..a.len() {
+        let diff = a[i] as i64 - if i < b.len() { b[i] as i64 } else { 0 } - borrow;
+        result.push(diff as u32);
+        borrow = if diff < 0 { 1 } else { 0 };
+    }
+    trim(&mut result);
+    return result;
+}
+fn mul_magnitude(a:&[u32], b:&[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut result = vec![0u32; a.len() + b.len()];
+    for i in 0..a.len() {
+        let mut carry = 0u64;
+        for j in 0..b.len() {
+            let product = a[i] as u64 * b[j] as u64 + result[i + j] as u64 + carry;
+            result[i + j] = product as u32;
+            carry = product >> 32;
+        }
+        result[i + b.len()] = carry as u32;
+    }
+    trim(&mut result);
+    return result;
+}
+/// Divide `a` by the single limb `divisor`, returning the quotient and
+/// remainder
+fn divrem_limb(a:&[u32], divisor:u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder = 0u64;
+    for i in (0..a.len()).rev() {
+        let current = (remainder << 32) | a[i] as u64;
+        quotient[i] = (current / divisor as u64) as u32;
+        remainder = current % divisor as u64;
+    }
+    trim(&mut quotient);
+    return (quotient, remainder as u32);
+}
+/// Shift `a` left by `shift` < 32 bits, with an extra limb for the overflow
+fn shl_bits(a:&[u32], shift:u32) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len() + 1);
+    let mut carry = 0u32;
+    for &limb in a {
+        result.push((limb << shift) | carry);
+        carry = if shift == 0 { 0 } else { limb >> (32 - shift) };
+    }
+    result.push(carry);
+    return result;
+}
+/// Divide `a` by `b`, which is non-zero, returning the quotient and remainder.
+/// Uses long division with quotient digit estimation (Knuth's algorithm D).
+fn divrem_magnitude(a:&[u32], b:&[u32]) -> (Vec<u32>, Vec<u32>) {
+    assert!(!b.is_empty(), "Division by zero");
+    if cmp_magnitude(a, b) == Ordering::Less {
+        return (Vec::new(), a.to_vec());
+    }
+    if b.len() == 1 {
+        let (quotient, remainder) = divrem_limb(a, b[0]);
+        let mut remainder = vec![remainder];
+        trim(&mut remainder);
+        return (quotient, remainder);
+    }
+    // Normalise so the top limb of the divisor has its high bit set, which
+    // keeps each quotient digit estimate within 2 of the true digit
+    let shift = b.last().unwrap().leading_zeros();
+    let divisor = shl_bits(b, shift);
+    let n = b.len();
+    let top = divisor[n - 1] as u64;
+    let next = divisor[n - 2] as u64;
+    let mut dividend = shl_bits(a, shift);
+    let m = a.len() - n;
+    let mut quotient = vec![0u32; m + 1];
+    for j in (0..=m).rev() {
+        let numerator = ((dividend[j + n] as u64) << 32) | dividend[j + n - 1] as u64;
+        let mut q = numerator / top;
+        let mut r = numerator % top;
+        while q >= 1 << 32 || q * next > ((r << 32) | dividend[j + n - 2] as u64) {
+            q -= 1;
+            r += top;
+            if r >= 1 << 32 {
+                break;
+            }
+        }
+        // Subtract q times the divisor from the current window
+        let mut borrow = 0i64;
+        let mut carry = 0u64;
+        for i in 0..n {
+            let product = q * divisor[i] as u64 + carry;
+            carry = product >> 32;
+            let diff = dividend[i + j] as i64 - borrow - (product & 0xffff_ffff) as i64;
+            dividend[i + j] = diff as u32;
+            borrow = if diff < 0 { 1 } else { 0 };
+        }
+        let diff = dividend[j + n] as i64 - borrow - carry as i64;
+        dividend[j + n] = diff as u32;
+        // The estimate was one too large, so add the divisor back
+        if diff < 0 {
+            q -= 1;
+            let mut carry = 0u64;
+            for i in 0..n {
+                let sum = dividend[i + j] as u64 + divisor[i] as u64 + carry;
+                dividend[i + j] = sum as u32;
+                carry = sum >> 32;
+            }
+            dividend[j + n] = dividend[j + n].wrapping_add(carry as u32);
+        }
+        quotient[j] = q as u32;
+    }
+    trim(&mut quotient);
+    // Undo the normalisation shift on the remainder
+    let mut remainder = vec![0u32; n];
+    for i in 0..n {
+        remainder[i] = if shift == 0 { dividend[i] } else { (dividend[i] >> shift) | (dividend[i + 1] << (32 - shift)) };
+    }
+    trim(&mut remainder);
+    return (quotient, remainder);
+}
+
+impl BigInt {
+    fn from_parts(negative:bool, mut magnitude:Vec<u32>) -> BigInt {
+        trim(&mut magnitude);
+        let negative = negative && !magnitude.is_empty();
+        return BigInt { negative, magnitude };
+    }
+    pub fn zero() -> BigInt {
+        return BigInt::default();
+    }
+    pub fn is_zero(&self) -> bool {
+        return self.magnitude.is_empty();
+    }
+    pub fn is_negative(&self) -> bool {
+        return self.negative;
+    }
+    /// The absolute value
+    pub fn abs(&self) -> BigInt {
+        return BigInt { negative:false, magnitude:self.magnitude.clone() };
+    }
+    /// The quotient and remainder of dividing by `divisor`, rounding the
+    /// quotient towards zero so the remainder has the sign of `self`. Panics
+    /// if `divisor` is zero.
+    pub fn div_rem(&self, divisor:&BigInt) -> (BigInt, BigInt) {
+        let (quotient, remainder) = divrem_magnitude(&self.magnitude, &divisor.magnitude);
+        return (BigInt::from_parts(self.negative != divisor.negative, quotient), BigInt::from_parts(self.negative, remainder));
+    }
+    /// The greatest common divisor of the absolute values, which is zero only
+    /// if both are zero
+    pub fn gcd(&self, other:&BigInt) -> BigInt {
+        let mut a = self.abs();
+        let mut b = other.abs();
+        while !b.is_zero() {
+            let (_, remainder) = a.div_rem(&b);
+            a = b;
+            b = remainder;
+        }
+        return a;
+    }
+    /// Raise to the power `exponent` by repeated squaring
+    pub fn pow(&self, exponent:u32) -> BigInt {
+        let mut result = BigInt::from(1);
+        let mut base = self.clone();
+        let mut exponent = exponent;
+        while exponent > 0 {
+            if exponent % 2 == 1 {
+                result = &result * &base;
+            }
+            base = &base * &base;
+            exponent /= 2;
+        }
+        return result;
+    }
+    /// The value as an `i128`, or `None` if it is out of range
+    pub fn to_i128(&self) -> Option<i128> {
+        if self.magnitude.len() > 4 {
+            return None;
+        }
+        let mut value:u128 = 0;
+        for i in (0..self.magnitude.len()).rev() {
+            value = (value << 32) | self.magnitude[i] as u128;
+        }
+        if self.negative {
+            return 0i128.checked_sub_unsigned(value);
+        }
+        return i128::try_from(value).ok();
+    }
+}
+impl From<i128> for BigInt {
+    fn from(n:i128) -> BigInt {
+        let mut value = n.unsigned_abs();
+        let mut magnitude = Vec::new();
+        while value > 0 {
+            magnitude.push(value as u32);
+            value >>= 32;
+        }
+        return BigInt { negative:n < 0, magnitude };
+    }
+}
+/// Implement `From` for primitive integers that widen losslessly to `i128`
+macro_rules! from_primitive {
+    ($($t:ty),*) => {
+        $(impl From<$t> for BigInt {
+            fn from(n:$t) -> BigInt {
+                return BigInt::from(n as i128);
+            }
+        })*
+    };
+}
+from_primitive!(i32, i64, isize, u32, u64, usize);
+impl FromStr for BigInt {
+    type Err = Error;
+    /// Parse a base 10 integer with an optional leading sign
+    fn from_str(s:&str) -> Result<BigInt, Error> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s))
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(Error::Input(format!("could not parse '{}' as an integer", s)));
+        }
+        // Accumulate base 10^9 chunks from the most significant end
+        let mut magnitude:Vec<u32> = Vec::new();
+        let first_chunk = (digits.len() - 1) % 9 + 1;
+        let mut start = 0;
+        let mut end = first_chunk;
+        while start < digits.len() {
+            let chunk:u64 = digits[start..end].parse().unwrap();
+            let scale = 10u64.pow((end - start) as u32);
+            let mut carry = chunk;
+            for limb in magnitude.iter_mut() {
+                let value = *limb as u64 * scale + carry;
+                *limb = value as u32;
+                carry = value >> 32;
+            }
+            if carry > 0 {
+                magnitude.push(carry as u32);
+            }
+            start = end;
+            end += 9;
+        }
+        return Ok(BigInt::from_parts(negative, magnitude));
+    }
+}
+impl std::fmt::Display for BigInt {
+    fn fmt(&self, f: &mut  std::fmt::Formatter) ->  std::fmt::Result {
+        // Split into base 10^9 chunks, least significant first
+        let mut chunks = Vec::new();
+        let mut rest = self.magnitude.clone();
+        while !rest.is_empty() {
+            let (quotient, remainder) = divrem_limb(&rest, 1_000_000_000);
+            chunks.push(remainder);
+            rest = quotient;
+        }
+        let mut digits = match chunks.pop() {
+            None => "0".to_string(),
+            Some(chunk) => chunk.to_string()
+        };
+        for chunk in chunks.iter().rev() {
+            digits.push_str(&format!("{:09}", chunk));
+        }
+        return f.pad_integral(!self.negative, "", &digits);
+    }
+}
+impl Ord for BigInt {
+    fn cmp(&self, other:&BigInt) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => return Ordering::Greater,
+            (true, false) => return Ordering::Less,
+            (false, false) => return cmp_magnitude(&self.magnitude, &other.magnitude),
+            (true, true) => return cmp_magnitude(&other.magnitude, &self.magnitude)
+        }
+    }
+}
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other:&BigInt) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+impl std::ops::Neg for &BigInt {
+    type Output = BigInt;
+    fn neg(self) -> BigInt {
+        return BigInt::from_parts(!self.negative, self.magnitude.clone());
+    }
+}
+impl std::ops::Add<&BigInt> for &BigInt {
+    type Output = BigInt;
+    fn add(self, rhs:&BigInt) -> BigInt {
+        if self.negative == rhs.negative {
+            return BigInt::from_parts(self.negative, add_magnitude(&self.magnitude, &rhs.magnitude));
+        }
+        // Opposite signs, so subtract the smaller magnitude from the larger
+        // and take the sign of the larger
+        match cmp_magnitude(&self.magnitude, &rhs.magnitude) {
+            Ordering::Less => return BigInt::from_parts(rhs.negative, sub_magnitude(&rhs.magnitude, &self.magnitude)),
+            _ => return BigInt::from_parts(self.negative, sub_magnitude(&self.magnitude, &rhs.magnitude))
+        }
+    }
+}
+impl std::ops::Sub<&BigInt> for &BigInt {
+    type Output = BigInt;
+    fn sub(self, rhs:&BigInt) -> BigInt {
+        return self + &(-rhs);
+    }
+}
+impl std::ops::Mul<&BigInt> for &BigInt {
+    type Output = BigInt;
+    fn mul(self, rhs:&BigInt) -> BigInt {
+        return BigInt::from_parts(self.negative != rhs.negative, mul_magnitude(&self.magnitude, &rhs.magnitude));
+    }
+}
+impl std::ops::Div<&BigInt> for &BigInt {
+    type Output = BigInt;
+    fn div(self, rhs:&BigInt) -> BigInt {
+        return self.div_rem(rhs).0;
+    }
+}
+impl std::ops::Rem<&BigInt> for &BigInt {
+    type Output = BigInt;
+    fn rem(self, rhs:&BigInt) -> BigInt {
+        return self.div_rem(rhs).1;
+    }
+}
+/// Implement an operator on owned `BigInt`s in terms of the one on references
+macro_rules! owned_op {
+    ($op:ident, $method:ident) => {
+        impl std::ops::$op<BigInt> for BigInt {
+            type Output = BigInt;
+            fn $method(self, rhs:BigInt) -> BigInt {
+                return (&self).$method(&rhs);
+            }
+        }
+    };
+}
+owned_op!(Add, add);
+owned_op!(Sub, sub);
+owned_op!(Mul, mul);
+owned_op!(Div, div);
+owned_op!(Rem, rem);
+impl std::ops::Neg for BigInt {
+    type Output = BigInt;
+    fn neg(self) -> BigInt {
+        return -&self;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pseudo-random `i128`s of varying sizes from a linear congruential
+    /// generator
+    fn values(n:usize) -> Vec<i128> {
+        let mut state:u64 = 98765;
+        let mut next = || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            return state;
+        };
+        return (0..n).map(|_| {
+            let value = ((next() as i128) << 64 | next() as i128) >> (next() % 126);
+            return if next() % 2 == 0 { value } else { -value };
+        }).collect();
+    }
+    #[test]
+    fn arithmetic_matches_i128() {
+        let values = values(80);
+        for &a in &values {
+            for &b in &values {
+                let (x, y) = (BigInt::from(a), BigInt::from(b));
+                if let Some(sum) = a.checked_add(b) {
+                    assert_eq!((&x + &y).to_i128(), Some(sum), "{} + {}", a, b);
+                }
+                if let Some(diff) = a.checked_sub(b) {
+                    assert_eq!((&x - &y).to_i128(), Some(diff), "{} - {}", a, b);
+                }
+                if let Some(product) = a.checked_mul(b) {
+                    assert_eq!((&x * &y).to_i128(), Some(product), "{} * {}", a, b);
+                }
+                if b != 0 {
+                    let (quotient, remainder) = x.div_rem(&y);
+                    assert_eq!((quotient.to_i128(), remainder.to_i128()), (Some(a / b), Some(a % b)), "{} / {}", a, b);
+                }
+                assert_eq!(x.cmp(&y), a.cmp(&b));
+            }
+            assert_eq!(BigInt::from(a).to_string(), a.to_string());
+        }
+    }
+    #[test]
+    fn beyond_i128() {
+        let big = BigInt::from(i128::MAX) * BigInt::from(i128::MAX);
+        assert_eq!(big.to_string(), "28948022309329048855892746252171976962977213799489202546401021394546514198529");
+        assert_eq!(big.to_i128(), None);
+        assert_eq!(big.div_rem(&BigInt::from(i128::MAX)), (BigInt::from(i128::MAX), BigInt::zero()));
+        assert_eq!((big.clone() + BigInt::from(5)).div_rem(&BigInt::from(i128::MAX)).1, BigInt::from(5));
+        assert_eq!(big.gcd(&(BigInt::from(i128::MAX) * BigInt::from(6))), BigInt::from(i128::MAX));
+        assert_eq!(BigInt::from(i128::MIN).to_i128(), Some(i128::MIN));
+        assert_eq!(BigInt::from(i128::MAX).pow(2), big);
+        assert_eq!(BigInt::from(-3).pow(3), BigInt::from(-27));
+        assert_eq!(BigInt::from(7).pow(0), BigInt::from(1));
+    }
+    #[test]
+    fn parsing() {
+        let text = "-123456789012345678901234567890123456789012345678901234567890";
+        let n:BigInt = text.parse().unwrap();
+        assert_eq!(n.to_string(), text);
+        assert_eq!("+42".parse::<BigInt>(), Ok(BigInt::from(42)));
+        assert_eq!("-0".parse::<BigInt>(), Ok(BigInt::zero()));
+        assert_eq!(format!("{:>5}", BigInt::from(-12)), "  -12");
+        assert!("12a".parse::<BigInt>().is_err());
+        assert!("-".parse::<BigInt>().is_err());
+        for &value in &values(40) {
+            assert_eq!(value.to_string().parse::<BigInt>(), Ok(BigInt::from(value)));
+        }
+    }
+}
@@ -0,0 +1,519 @@
+use super::{BigInt, Integer, LinearProgram, LpSolution, Rational, Relation};
+use crate::error::Error;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+/// Fixed length vector of bits packed into 64-bit words, used as a vector
+/// over GF(2)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitVec {
+    words:Vec<u64>,
+    len:usize
+}
+impl BitVec {
+    /// A vector of `len` zero bits
+    pub fn new(len:usize) -> BitVec {
+        return BitVec { words:vec![0; len.div_ceil(64)], len };
+    }
+    pub fn from_bools(bits:&[bool]) -> BitVec {
+        let mut v = BitVec::new(bits.len());
+        for i in 0..bits.len() {
+            v.set(i, bits[i]);
+        }
+        return v;
+    }
+    pub fn len(&self) -> usize {
+        return self.len;
+    }
+    pub fn is_empty(&self) -> bool {
+        return self.len == 0;
+    }
+    pub fn get(&self, i:usize) -> bool {
+        assert!(i < self.len, "Bit {} out of range for length {}", i, self.len);
+        return self.words[i / 64] >> (i % 64) & 1 == 1;
+    }
+    pub fn set(&mut self, i:usize, value:bool) {
+        assert!(i < self.len, "Bit {} out of range for length {}", i, self.len);
+        if value {
+            self.words[i / 64] |= 1 << (i % 64);
+        }
+        else {
+            self.words[i / 64] &= !(1 << (i % 64));
+        }
+    }
+    /// Add `other` to this vector, which over GF(2) is a bitwise xor
+    pub fn xor_assign(&mut self, other:&BitVec) {
+        assert_eq!(self.len, other.len, "Bit vectors differ in length");
+        for i in 0..self.words.len() {
+            self.words[i] ^= other.words[i];
+        }
+    }
+    /// Number of set bits
+    pub fn count_ones(&self) -> usize {
+        return self.words.iter().map(|w| w.count_ones() as usize).sum();
+    }
+    /// Indices of the set bits in increasing order
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        return self.words.iter().enumerate().flat_map(|(w, &word)| {
+            let mut rest = word;
+            return std::iter::from_fn(move || {
+                if rest == 0 {
+                    return None;
+                }
+                let bit = rest.trailing_zeros() as usize;
+                rest &= rest - 1;
+                return Some(w * 64 + bit);
+            });
+        });
+    }
+}
+impl std::fmt::Display for BitVec {
+    fn fmt(&self, f: &mut  std::fmt::Formatter) ->  std::fmt::Result {
+        for i in 0..self.len {
+            write!(f, "{}", if self.get(i) { '1' } else { '0' })?;
+        }
+        return Ok(());
+    }
+}
+
+/// Largest null space dimension searched exhaustively for a cheapest
+/// solution, which bounds a search at 2^22 candidates
+pub const MAX_SEARCH_BITS:usize = 22;
+/// Largest column space dimension searched with Dijkstra's algorithm, which
+/// keeps the distance and predecessor tables of its 2^16 states near 2 MB
+pub const MAX_PATH_BITS:usize = 16;
+
+/// Outcome of `BitMatrix::min_cost_solution`
+#[derive(Debug, Clone, PartialEq)]
+pub enum MinCostSolution {
+    /// A solution with the lowest total cost
+    Optimal(BitVec),
+    /// No solution exists
+    Inconsistent
+}
+
+/// All solutions of a consistent linear system over GF(2): the particular
+/// solution plus any combination of the null space basis vectors
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gf2Solution {
+    /// The solution with every free variable zero
+    pub particular:BitVec,
+    /// A null space basis vector for each free variable
+    pub basis:Vec<BitVec>,
+    /// The free variable of each basis vector
+    pub free:Vec<usize>
+}
+impl Gf2Solution {
+    /// The solution with the fewest set bits, or `None` if there are more
+    /// than `MAX_SEARCH_BITS` free variables
+    pub fn min_weight(&self) -> Option<BitVec> {
+        return self.min_by_key(|x| x.count_ones() as u128);
+    }
+    /// The solution with the lowest total of `costs` over its set bits, or
+    /// `None` if there are more than `MAX_SEARCH_BITS` free variables. Totals
+    /// are summed as `u128`, which no sum of `usize` costs can overflow.
+    pub fn min_cost(&self, costs:&[usize]) -> Option<BitVec> {
+        assert_eq!(costs.len(), self.particular.len(), "Need a cost for each variable");
+        return self.min_by_key(|x| x.ones().map(|i| costs[i] as u128).sum());
+    }
+    /// The solution minimising `key`, the first found on ties. The null space
+    /// is walked in Gray code order so each step adds a single basis vector,
+    /// which takes 2^(free variables) steps.
+    fn min_by_key(&self, key:impl Fn(&BitVec) -> u128) -> Option<BitVec> {
+        if self.basis.len() > MAX_SEARCH_BITS {
+            return None;
+        }
+        let mut x = self.particular.clone();
+        let mut best = x.clone();
+        let mut best_key = key(&x);
+        for step in 1..1u64 << self.basis.len() {
+            // The bit that changes between consecutive Gray codes
+            x.xor_assign(&self.basis[step.trailing_zeros() as usize]);
+            let k = key(&x);
+            if k < best_key {
+                best = x.clone();
+                best_key = k;
+            }
+        }
+        return Some(best);
+    }
+}
+
+/// Matrix over GF(2) with each row stored as a `BitVec`
+#[derive(Debug, Clone, PartialEq)]
+pub struct BitMatrix {
+    rows:Vec<BitVec>,
+    cols:usize
+}
+impl BitMatrix {
+    /// A zero matrix
+    pub fn new(rows:usize, cols:usize) -> BitMatrix {
+        return BitMatrix { rows:vec![BitVec::new(cols); rows], cols };
+    }
+    pub fn rows(&self) -> usize {
+        return self.rows.len();
+    }
+    pub fn cols(&self) -> usize {
+        return self.cols;
+    }
+    pub fn row(&self, r:usize) -> &BitVec {
+        return &self.rows[r];
+    }
+    /// The entries of column `c`
+    pub fn column(&self, c:usize) -> BitVec {
+        return BitVec::from_bools(&self.rows.iter().map(|row| row.get(c)).collect::<Vec<bool>>());
+    }
+    pub fn get(&self, r:usize, c:usize) -> bool {
+        return self.rows[r].get(c);
+    }
+    pub fn set(&mut self, r:usize, c:usize, value:bool) {
+        self.rows[r].set(c, value);
+    }
+    /// Product with the column vector `x`
+    pub fn mul_vec(&self, x:&BitVec) -> BitVec {
+        assert_eq!(x.len(), self.cols, "Vector length doesn't match the columns");
+        let mut result = BitVec::new(self.rows());
+        for r in 0..self.rows() {
+            let mut row = self.rows[r].clone();
+            for i in 0..row.words.len() {
+                row.words[i] &= x.words[i];
+            }
+            result.set(r, row.count_ones() % 2 == 1);
+        }
+        return result;
+    }
+    /// Reduce the rows and `target` to reduced row echelon form by
+    /// Gauss-Jordan elimination, one word of each row at a time, returning
+    /// them with the pivot column of each non-zero row
+    fn eliminate(&self, target:&BitVec) -> (Vec<BitVec>, Vec<bool>, Vec<usize>) {
+        assert_eq!(target.len(), self.rows(), "Target length doesn't match the rows");
+        let mut rows = self.rows.clone();
+        let mut rhs:Vec<bool> = (0..self.rows()).map(|r| target.get(r)).collect();
+        let mut pivots:Vec<usize> = Vec::new();
+        for c in 0..self.cols {
+            let r = pivots.len();
+            let Some(p) = (r..rows.len()).find(|&p| rows[p].get(c)) else {
+                continue;
+            };
+            rows.swap(r, p);
+            rhs.swap(r, p);
+            let pivot_row = rows[r].clone();
+            for other in 0..rows.len() {
+                if other != r && rows[other].get(c) {
+                    rows[other].xor_assign(&pivot_row);
+                    rhs[other] ^= rhs[r];
+                }
+            }
+            pivots.push(c);
+        }
+        return (rows, rhs, pivots);
+    }
+    /// The pivot columns of the reduced row echelon form, in order
+    pub fn pivots(&self) -> Vec<usize> {
+        return self.eliminate(&BitVec::new(self.rows())).2;
+    }
+    pub fn rank(&self) -> usize {
+        return self.pivots().len();
+    }
+    /// Solve `self * x = target`. Returns `None` if the system is
+    /// inconsistent.
+    pub fn solve(&self, target:&BitVec) -> Option<Gf2Solution> {
+        let (rows, rhs, pivots) = self.eliminate(target);
+        // Any remaining row is all zero, so needs a zero target
+        if rhs[pivots.len()..].iter().any(|&b| b) {
+            return None;
+        }
+        let mut particular = BitVec::new(self.cols);
+        for i in 0..pivots.len() {
+            particular.set(pivots[i], rhs[i]);
+        }
+        let free:Vec<usize> = (0..self.cols).filter(|c| !pivots.contains(c)).collect();
+        let mut basis = Vec::new();
+        for &f in &free {
+            let mut v = BitVec::new(self.cols);
+            v.set(f, true);
+            for i in 0..pivots.len() {
+                if rows[i].get(f) {
+                    v.set(pivots[i], true);
+                }
+            }
+            basis.push(v);
+        }
+        return Some(Gf2Solution { particular, basis, free });
+    }
+    /// Find the solution of `self * x = target` with the lowest total of
+    /// `costs` over its set bits. Zero columns are never worth setting, and
+    /// of identical columns only the cheapest is, so those are dropped first.
+    /// Then either the null space is walked in Gray code order, or, if the
+    /// column space is smaller, the cheapest way to reach the target is
+    /// found with Dijkstra's algorithm over the column space. If the null
+    /// space has more than `MAX_SEARCH_BITS` dimensions and the column space
+    /// more than `MAX_PATH_BITS` it falls back to branch and bound, which
+    /// returns `Error::Overflow` if even `BigInt` arithmetic can't hold it.
+    pub fn min_cost_solution(&self, target:&BitVec, costs:&[usize]) -> Result<MinCostSolution, Error> {
+        assert_eq!(costs.len(), self.cols, "Need a cost for each column");
+        // Costs are never negative, so pressing nothing is cheapest
+        if target.count_ones() == 0 {
+            return Ok(MinCostSolution::Optimal(BitVec::new(self.cols)));
+        }
+        let mut kept:Vec<usize> = Vec::new();
+        let mut distinct:HashMap<BitVec, usize> = HashMap::new();
+        for c in 0..self.cols {
+            let column = self.column(c);
+            if column.count_ones() == 0 {
+                continue;
+            }
+            match distinct.get(&column) {
+                Some(&i) if costs[c] < costs[kept[i]] => kept[i] = c,
+                Some(_) => {},
+                None => {
+                    distinct.insert(column, kept.len());
+                    kept.push(c);
+                }
+            }
+        }
+        let mut reduced = BitMatrix::new(self.rows(), kept.len());
+        for i in 0..kept.len() {
+            for r in 0..self.rows() {
+                reduced.set(r, i, self.get(r, kept[i]));
+            }
+        }
+        let reduced_costs:Vec<usize> = kept.iter().map(|&c| costs[c]).collect();
+        let Some(solution) = reduced.solve(target) else {
+            return Ok(MinCostSolution::Inconsistent);
+        };
+        let free = solution.free.len();
+        let rank = kept.len() - free;
+        let x = if free <= MAX_SEARCH_BITS && (free <= rank || rank > MAX_PATH_BITS) {
+            solution.min_cost(&reduced_costs).unwrap()
+        }
+        else if rank <= MAX_PATH_BITS {
+            reduced.cheapest_path(target, &reduced_costs)
+        }
+        else {
+            reduced.cheapest_parity_solution(target, &reduced_costs)?
+        };
+        let mut full = BitVec::new(self.cols);
+        for i in x.ones() {
+            full.set(kept[i], true);
+        }
+        return Ok(MinCostSolution::Optimal(full));
+    }
+    /// Cheapest solution of a consistent system found with Dijkstra's
+    /// algorithm. In the coordinates of the pivot rows each column flips some
+    /// of at most `MAX_PATH_BITS` bits, so the states are every value of
+    /// those bits and the target is a state reached from zero. Distances are
+    /// `u128`, which a path of at most 2^`MAX_PATH_BITS` `usize` costs can't
+    /// overflow.
+    fn cheapest_path(&self, target:&BitVec, costs:&[usize]) -> BitVec {
+        let (rows, rhs, pivots) = self.eliminate(target);
+        let rank = pivots.len();
+        let flips:Vec<usize> = (0..self.cols).map(|c| (0..rank).filter(|&i| rows[i].get(c)).fold(0, |state, i| state | 1 << i)).collect();
+        let goal = (0..rank).filter(|&i| rhs[i]).fold(0, |state, i| state | 1 << i);
+        let mut dist = vec![u128::MAX; 1 << rank];
+        // The state each state was reached from and the column flipped
+        let mut previous = vec![(0, 0); 1 << rank];
+        let mut queue = BinaryHeap::new();
+        dist[0] = 0;
+        queue.push(Reverse((0, 0)));
+        while let Some(Reverse((d, state))) = queue.pop() {
+            if state == goal {
+                break;
+            }
+            if d > dist[state] {
+                continue;
+            }
+            for c in 0..self.cols {
+                let next = state ^ flips[c];
+                let next_dist = d + costs[c] as u128;
+                if next_dist < dist[next] {
+                    dist[next] = next_dist;
+                    previous[next] = (state, c);
+                    queue.push(Reverse((next_dist, next)));
+                }
+            }
+        }
+        // A column used twice on the path cancels out, and costs nothing
+        // more to leave out
+        let mut x = BitVec::new(self.cols);
+        let mut state = goal;
+        while state != 0 {
+            let (from, c) = previous[state];
+            x.set(c, !x.get(c));
+            state = from;
+        }
+        return x;
+    }
+    /// Cheapest solution of a consistent system found by branch and bound on
+    /// the integer program `self * x - 2k = target` over `x, k >= 0`. Taking
+    /// each `x` modulo 2 keeps a solution and costs no more, so its optimum
+    /// gives a cheapest solution over GF(2). The search only explores
+    /// branches no more costly than the optimum, which bounds each `x` with a
+    /// cost and so each `k`, and the rest are bounded by 1 so it is finite.
+    /// Solved over `i128`, or `BigInt` if that overflows.
+    fn cheapest_parity_solution(&self, target:&BitVec, costs:&[usize]) -> Result<BitVec, Error> {
+        match self.parity_program::<i128>(target, costs) {
+            Err(Error::Overflow(_)) => return self.parity_program::<BigInt>(target, costs),
+            result => return result
+        }
+    }
+    /// Solve the integer program of `cheapest_parity_solution` over
+    /// rationals of `I`, returning `Error::Overflow` if the arithmetic
+    /// doesn't fit
+    fn parity_program<I: Integer + From<i128>>(&self, target:&BitVec, costs:&[usize]) -> Result<BitVec, Error> {
+        let int = |v:i128| Rational::new_int(I::from(v));
+        let mut objective:Vec<Rational<I>> = costs.iter().map(|&c| int(c as i128)).collect();
+        objective.extend((0..self.rows()).map(|_| Rational::zero()));
+        let mut program = LinearProgram::new(objective);
+        for r in 0..self.rows() {
+            let mut coefficients:Vec<Rational<I>> = (0..self.cols).map(|c| int(self.get(r, c) as i128)).collect();
+            coefficients.extend((0..self.rows()).map(|i| int(if i == r { -2 } else { 0 })));
+            program.constrain(coefficients, Relation::Equal, int(target.get(r) as i128));
+        }
+        for c in (0..self.cols).filter(|&c| costs[c] == 0) {
+            let mut coefficients = vec![Rational::zero(); self.cols + self.rows()];
+            coefficients[c] = int(1);
+            program.constrain(coefficients, Relation::LessEq, int(1));
+        }
+        match program.solve_integer()?.0 {
+            LpSolution::Optimal { x, .. } => {
+                let two = I::from(2);
+                let mut bits = BitVec::new(self.cols);
+                for c in 0..self.cols {
+                    bits.set(c, !x[c].num.div_rem_floor(&two).1.is_zero());
+                }
+                return Ok(bits);
+            },
+            _ => return Err(Error::NoSolution("the parity program of a consistent system has no optimum".to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bit_vectors() {
+        let mut v = BitVec::new(130);
+        v.set(0, true);
+        v.set(64, true);
+        v.set(129, true);
+        assert_eq!(v.count_ones(), 3);
+        assert_eq!(v.ones().collect::<Vec<usize>>(), vec![0, 64, 129]);
+        let mut w = v.clone();
+        w.xor_assign(&v);
+        assert_eq!(w, BitVec::new(130));
+        assert_eq!(BitVec::from_bools(&[true, false, true]).to_string(), "101");
+    }
+    #[test]
+    fn solve_and_min_weight() {
+        // x0 + x1 = 1, x1 + x2 = 0, with x3 unconstrained
+        let mut m = BitMatrix::new(2, 4);
+        for (r, c) in [(0, 0), (0, 1), (1, 1), (1, 2)] {
+            m.set(r, c, true);
+        }
+        let target = BitVec::from_bools(&[true, false]);
+        let solution = m.solve(&target).unwrap();
+        assert_eq!(solution.free, vec![2, 3]);
+        for v in &solution.basis {
+            assert_eq!(m.mul_vec(v), BitVec::new(2));
+        }
+        let best = solution.min_weight().unwrap();
+        assert_eq!(m.mul_vec(&best), target);
+        assert_eq!(best.count_ones(), 1);
+        // Making x0 expensive gives x1 + x2 instead
+        assert_eq!(solution.min_cost(&[5, 1, 1, 1]), Some(BitVec::from_bools(&[false, true, true, false])));
+        // x0 + x1 = 1 and x0 + x1 = 0 contradict
+        let mut contradiction = BitMatrix::new(2, 2);
+        for (r, c) in [(0, 0), (0, 1), (1, 0), (1, 1)] {
+            contradiction.set(r, c, true);
+        }
+        assert_eq!(contradiction.solve(&BitVec::from_bools(&[true, false])), None);
+        assert_eq!((m.rank(), contradiction.rank()), (2, 1));
+    }
+    #[test]
+    fn wide_system() {
+        // 100 lights, each toggled by its own button and the button after it,
+        // so the only way to light just the last is every button once
+        let n = 100;
+        let mut m = BitMatrix::new(n, n);
+        for i in 0..n {
+            m.set(i, i, true);
+            if i + 1 < n {
+                m.set(i, i + 1, true);
+            }
+        }
+        let mut target = BitVec::new(n);
+        target.set(n - 1, true);
+        let solution = m.solve(&target).unwrap();
+        assert!(solution.basis.is_empty());
+        assert_eq!(solution.min_weight().unwrap().count_ones(), n);
+    }
+    #[test]
+    fn large_null_space() {
+        // 10 rows and 1023 distinct columns, every non-zero pattern, plus
+        // copies of the unit columns and zero columns, so the null space has
+        // over a thousand dimensions but the column space only ten
+        let rows = 10;
+        let mut columns:Vec<usize> = (1..1 << rows).collect();
+        columns.extend((0..rows).map(|i| 1 << i));
+        columns.extend([0, 0]);
+        let mut m = BitMatrix::new(rows, columns.len());
+        for c in 0..columns.len() {
+            for r in 0..rows {
+                m.set(r, c, columns[c] & 1 << r != 0);
+            }
+        }
+        let mut costs = vec![1; columns.len()];
+        // The all ones column is expensive, so two cheaper columns win
+        costs[(1 << rows) - 2] = 3;
+        let target = BitVec::from_bools(&[true; 10]);
+        let MinCostSolution::Optimal(x) = m.min_cost_solution(&target, &costs).unwrap() else {
+            panic!("expected a solution");
+        };
+        assert_eq!(m.mul_vec(&x), target);
+        assert_eq!(x.ones().map(|c| costs[c]).sum::<usize>(), 2);
+        assert_eq!(m.solve(&target).unwrap().min_weight(), None);
+    }
+    #[test]
+    fn large_column_and_null_space() {
+        // 23 rows with unit columns and columns for pairs of rows one and two
+        // apart, so both the column space and the null space are too large to
+        // search exhaustively
+        let rows = 23;
+        let mut m = BitMatrix::new(rows, 3 * rows - 3);
+        for i in 0..rows {
+            m.set(i, i, true);
+        }
+        for i in 0..rows - 1 {
+            m.set(i, rows + i, true);
+            m.set(i + 1, rows + i, true);
+        }
+        for i in 0..rows - 2 {
+            m.set(i, 2 * rows - 1 + i, true);
+            m.set(i + 2, 2 * rows - 1 + i, true);
+        }
+        let costs = vec![1; m.cols()];
+        assert_eq!(m.min_cost_solution(&BitVec::new(rows), &costs), Ok(MinCostSolution::Optimal(BitVec::new(m.cols()))));
+        // Each column covers at most two rows, so covering all 23 takes 12
+        let target = BitVec::from_bools(&[true; 23]);
+        let MinCostSolution::Optimal(x) = m.min_cost_solution(&target, &costs).unwrap() else {
+            panic!("expected a solution");
+        };
+        assert_eq!(m.mul_vec(&x), target);
+        assert_eq!(x.count_ones(), 12);
+        // A pair column flips an even number of rows, so an odd number of
+        // unit columns is needed, and with units costing 3 and pairs 2 the
+        // cheapest is one unit and 11 pairs
+        let costs:Vec<usize> = (0..m.cols()).map(|c| if c < rows { 3 } else { 2 }).collect();
+        let MinCostSolution::Optimal(x) = m.min_cost_solution(&target, &costs).unwrap() else {
+            panic!("expected a solution");
+        };
+        assert_eq!(m.mul_vec(&x), target);
+        assert_eq!(x.ones().map(|c| costs[c]).sum::<usize>(), 25);
+        let mut inconsistent = BitMatrix::new(2, 1);
+        inconsistent.set(0, 0, true);
+        assert_eq!(inconsistent.min_cost_solution(&BitVec::from_bools(&[false, true]), &[1]), Ok(MinCostSolution::Inconsistent));
+    }
+}
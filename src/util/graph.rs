@@ -0,0 +1,327 @@
+use super::Integer;
+use crate::error::Error;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Directed graph with named nodes. Node names are interned, so nodes are
+/// referred to by their `usize` id, assigned in order of first use.
+#[derive(Debug, Clone, Default)]
+pub struct DiGraph {
+    names:Vec<String>,
+    ids:HashMap<String, usize>,
+    /// Successor ids of each node
+    edges:Vec<Vec<usize>>
+}
+impl DiGraph {
+    pub fn new() -> DiGraph {
+        return DiGraph::default();
+    }
+    /// The id of the node called `name`, adding the node if it doesn't exist
+    pub fn node(&mut self, name:&str) -> usize {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = self.names.len();
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        self.edges.push(Vec::new());
+        return id;
+    }
+    /// The id of the node called `name`, if it exists
+    pub fn id(&self, name:&str) -> Option<usize> {
+        return self.ids.get(name).copied();
+    }
+    /// The name of node `id`
+    pub fn name(&self, id:usize) -> &str {
+        return &self.names[id];
+    }
+    /// Add an edge from node `from` to node `to`
+    pub fn add_edge(&mut self, from:usize, to:usize) {
+        self.edges[from].push(to);
+    }
+    /// Number of nodes
+    pub fn len(&self) -> usize {
+        return self.names.len();
+    }
+    pub fn is_empty(&self) -> bool {
+        return self.names.is_empty();
+    }
+    /// Number of edges
+    pub fn edge_count(&self) -> usize {
+        return self.edges.iter().map(|e| e.len()).sum();
+    }
+    /// Ids of the nodes with an edge from node `id`
+    pub fn successors(&self, id:usize) -> &[usize] {
+        return &self.edges[id];
+    }
+    /// Predecessor ids of each node
+    fn predecessors(&self) -> Vec<Vec<usize>> {
+        let mut preds = vec![Vec::new(); self.len()];
+        for from in 0..self.len() {
+            for &to in &self.edges[from] {
+                preds[to].push(from);
+            }
+        }
+        return preds;
+    }
+    /// Ids of all nodes reachable from `start` (including itself) along
+    /// `edges`
+    fn reachable(edges:&Vec<Vec<usize>>, start:usize) -> HashSet<usize> {
+        let mut seen = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(id) = queue.pop_front() {
+            for &next in &edges[id] {
+                if seen.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        return seen;
+    }
+    /// Topologically sort the nodes in `subset` considering only edges
+    /// between them, using Kahn's algorithm. Returns an error describing a
+    /// cycle if there is one.
+    fn sort_subset(&self, subset:&HashSet<usize>) -> Result<Vec<usize>, Error> {
+        let mut in_degree:HashMap<usize, usize> = subset.iter().map(|&id| (id, 0)).collect();
+        for &from in subset {
+            for to in &self.edges[from] {
+                if let Some(d) = in_degree.get_mut(to) {
+                    *d += 1;
+                }
+            }
+        }
+        // Start from the nodes with no incoming edges, in id order so the
+        // result is deterministic
+        let mut ready:Vec<usize> = in_degree.iter().filter(|(_, d)| **d == 0).map(|(id, _)| *id).collect();
+        ready.sort();
+        let mut queue = VecDeque::from(ready);
+        let mut order = Vec::new();
+        while let Some(id) = queue.pop_front() {
+            order.push(id);
+            for to in &self.edges[id] {
+                if let Some(d) = in_degree.get_mut(to) {
+                    *d -= 1;
+                    if *d == 0 {
+                        queue.push_back(*to);
+                    }
+                }
+            }
+        }
+        if order.len() < subset.len() {
+            let remaining:HashSet<usize> = in_degree.iter().filter(|(_, d)| **d > 0).map(|(id, _)| *id).collect();
+            let cycle = self.cycle_in(&remaining);
+            let names:Vec<&str> = cycle.iter().map(|&id| self.name(id)).collect();
+            return Err(Error::Input(format!("graph has a cycle: {}", names.join(" -> "))));
+        }
+        return Ok(order);
+    }
+    /// Find a cycle among `remaining`, the nodes left over by Kahn's
+    /// algorithm, each of which has a predecessor among them. Returns the
+    /// cycle's nodes in edge order with the first node repeated at the end.
+    fn cycle_in(&self, remaining:&HashSet<usize>) -> Vec<usize> {
+        let preds = self.predecessors();
+        // Walk backwards through predecessors until a node repeats
+        let mut walk = vec![*remaining.iter().min().unwrap()];
+        let mut position:HashMap<usize, usize> = HashMap::from([(walk[0], 0)]);
+        loop {
+            let curr = *walk.last().unwrap();
+            let prev = *preds[curr].iter().filter(|p| remaining.contains(p)).min().unwrap();
+            if let Some(&start) = position.get(&prev) {
+                let mut cycle:Vec<usize> = walk[start..].to_vec();
+                cycle.push(prev);
+                cycle.reverse();
+                return cycle;
+            }
+            position.insert(prev, walk.len());
+            walk.push(prev);
+        }
+    }
+    /// Topologically sort all nodes, so every edge goes from an earlier node
+    /// to a later one. Returns an error describing a cycle if there is one.
+    pub fn topological_sort(&self) -> Result<Vec<usize>, Error> {
+        return self.sort_subset(&(0..self.len()).collect());
+    }
+    /// Topological order of the nodes on some path from `from` to `to`.
+    /// Cycles elsewhere in the graph don't affect the paths between them and
+    /// are ignored.
+    fn path_nodes(&self, from:usize, to:usize) -> Result<Vec<usize>, Error> {
+        let forward = DiGraph::reachable(&self.edges, from);
+        let backward = DiGraph::reachable(&self.predecessors(), to);
+        let subset:HashSet<usize> = forward.intersection(&backward).copied().collect();
+        return self.sort_subset(&subset);
+    }
+    /// Add `count` paths to `paths[next]`, returning `None` on overflow
+    fn add_paths<T: Integer>(paths:&mut HashMap<usize, T>, next:usize, count:&T) -> Option<()> {
+        let total = paths.entry(next).or_insert(T::zero());
+        *total = total.checked_add(count)?;
+        return Some(());
+    }
+    fn overflow<T>(&self, from:usize, to:usize) -> Error {
+        return Error::Overflow(format!("the number of paths from {} to {} does not fit in {}", self.name(from), self.name(to), std::any::type_name::<T>()));
+    }
+    /// Count the distinct paths from node `from` to node `to` as a `T`.
+    /// Returns an error if a cycle lies on a path between them, as there
+    /// would be infinitely many paths, or if the count overflows `T`.
+    pub fn count_paths<T: Integer>(&self, from:usize, to:usize) -> Result<T, Error> {
+        let order = self.path_nodes(from, to)?;
+        if order.is_empty() {
+            return Ok(T::zero());
+        }
+        // Accumulate the paths to each node in topological order
+        let mut paths:HashMap<usize, T> = HashMap::from([(from, T::one())]);
+        for id in order {
+            let count = paths.get(&id).cloned().unwrap_or(T::zero());
+            if id == to {
+                return Ok(count);
+            }
+            for &next in &self.edges[id] {
+                DiGraph::add_paths(&mut paths, next, &count).ok_or_else(|| self.overflow::<T>(from, to))?;
+            }
+        }
+        return Ok(T::zero());
+    }
+    /// Count the distinct paths from node `from` to every node reachable from
+    /// it. Returns an error if there is a cycle reachable from `from` or a
+    /// count overflows `T`.
+    pub fn count_paths_from<T: Integer>(&self, from:usize) -> Result<HashMap<usize, T>, Error> {
+        let order = self.sort_subset(&DiGraph::reachable(&self.edges, from))?;
+        let mut paths:HashMap<usize, T> = HashMap::from([(from, T::one())]);
+        for id in order {
+            let count = paths.get(&id).cloned().unwrap_or(T::zero());
+            for &next in &self.edges[id] {
+                DiGraph::add_paths(&mut paths, next, &count).ok_or_else(|| self.overflow::<T>(from, next))?;
+            }
+        }
+        return Ok(paths);
+    }
+    /// Count the distinct paths from node `from` to node `to` that pass
+    /// through every node in `waypoints`, in any order. Returns an error if a
+    /// cycle lies on a path between `from` and `to` or the count overflows
+    /// `T`.
+    pub fn count_paths_via<T: Integer>(&self, from:usize, to:usize, waypoints:&[usize]) -> Result<T, Error> {
+        let order = self.path_nodes(from, to)?;
+        let position:HashMap<usize, usize> = order.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+        // A path can only visit the waypoints in topological order, so the
+        // paths are the product of the paths between consecutive waypoints in
+        // that order. A waypoint off every path means there are none.
+        let mut stops = Vec::new();
+        for w in waypoints {
+            match position.get(w) {
+                Some(&p) => stops.push((p, *w)),
+                None => return Ok(T::zero())
+            }
+        }
+        stops.sort();
+        stops.dedup();
+        stops.push((order.len(), to));
+        let mut paths = T::one();
+        let mut curr = from;
+        for (_, w) in stops {
+            let segment:T = self.count_paths(curr, w)?;
+            paths = match paths.checked_mul(&segment) {
+                Some(product) => product,
+                None => return Err(self.overflow::<T>(from, to))
+            };
+            curr = w;
+        }
+        return Ok(paths);
+    }
+    /// Format the graph in Graphviz DOT format. `attributes` gives the DOT
+    /// attributes, as (name, value) pairs, for each node id.
+    pub fn to_dot(&self, attributes:impl Fn(usize) -> Vec<(String, String)>) -> String {
+        let mut dot = String::from("digraph {\n");
+        for id in 0..self.len() {
+            let attrs:Vec<String> = attributes(id).iter().map(|(k, v)| format!("{}={}", k, dot_id(v))).collect();
+            if !attrs.is_empty() {
+                dot.push_str(&format!("    {} [{}];\n", dot_id(self.name(id)), attrs.join(", ")));
+            }
+            else {
+                dot.push_str(&format!("    {};\n", dot_id(self.name(id))));
+            }
+        }
+        for from in 0..self.len() {
+            for &to in &self.edges[from] {
+                dot.push_str(&format!("    {} -> {};\n", dot_id(self.name(from)), dot_id(self.name(to))));
+            }
+        }
+        dot.push_str("}\n");
+        return dot;
+    }
+}
+
+/// Quote `s` as a DOT identifier. Line breaks become DOT line breaks.
+fn dot_id(s:&str) -> String {
+    return format!("\"{}\"", s.replace("\\", "\\\\").replace("\"", "\\\"").replace("\n", "\\n"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::BigInt;
+
+    fn graph(edges:&[(&str, &str)]) -> DiGraph {
+        let mut g = DiGraph::new();
+        for (from, to) in edges {
+            let (a, b) = (g.node(from), g.node(to));
+            g.add_edge(a, b);
+        }
+        return g;
+    }
+    #[test]
+    fn interning_and_sorting() {
+        let g = graph(&[("a", "b"), ("a", "c"), ("b", "d"), ("c", "d")]);
+        assert_eq!((g.len(), g.edge_count()), (4, 4));
+        assert_eq!(g.id("c"), Some(2));
+        assert_eq!(g.id("z"), None);
+        assert_eq!(g.name(3), "d");
+        assert_eq!(g.topological_sort(), Ok(vec![0, 1, 2, 3]));
+    }
+    #[test]
+    fn path_counts() {
+        let g = graph(&[("s", "a"), ("s", "b"), ("a", "c"), ("b", "c"), ("c", "d"), ("c", "e"), ("d", "t"), ("e", "t"), ("a", "t")]);
+        let id = |name| g.id(name).unwrap();
+        assert_eq!(g.count_paths::<i64>(id("s"), id("t")), Ok(5));
+        assert_eq!(g.count_paths::<i64>(id("t"), id("s")), Ok(0));
+        assert_eq!(g.count_paths::<i64>(id("c"), id("c")), Ok(1));
+        assert_eq!(g.count_paths_via::<i64>(id("s"), id("t"), &[id("d"), id("a")]), Ok(1));
+        assert_eq!(g.count_paths_via::<i64>(id("s"), id("t"), &[id("c")]), Ok(4));
+        assert_eq!(g.count_paths_via::<i64>(id("s"), id("t"), &[id("d"), id("e")]), Ok(0));
+    }
+    #[test]
+    fn paths_from_and_dot() {
+        let g = graph(&[("s", "a"), ("s", "b"), ("a", "t"), ("b", "t"), ("x", "s")]);
+        let paths = g.count_paths_from::<i64>(g.id("s").unwrap()).unwrap();
+        assert_eq!(paths[&g.id("t").unwrap()], 2);
+        assert_eq!(paths.get(&g.id("x").unwrap()), None);
+        let dot = g.to_dot(|id| if g.name(id) == "s" { vec![("label".to_string(), "start \"s\"\n2".to_string())] } else { vec![] });
+        assert!(dot.starts_with("digraph {\n    \"s\" [label=\"start \\\"s\\\"\\n2\"];\n    \"a\";\n"));
+        assert!(dot.contains("    \"x\" -> \"s\";\n"));
+        assert_eq!(dot.matches(" -> ").count(), g.edge_count());
+    }
+    #[test]
+    fn cycles() {
+        let g = graph(&[("s", "a"), ("a", "b"), ("b", "a"), ("b", "t"), ("s", "t"), ("x", "y"), ("y", "x")]);
+        let id = |name| g.id(name).unwrap();
+        assert_eq!(g.count_paths::<i64>(id("s"), id("t")), Err(Error::Input("graph has a cycle: a -> b -> a".to_string())));
+        assert!(g.topological_sort().is_err());
+        // The x-y cycle isn't on any path from s
+        let g = graph(&[("s", "t"), ("x", "y"), ("y", "x"), ("s", "x")]);
+        assert_eq!(g.count_paths::<i64>(g.id("s").unwrap(), g.id("t").unwrap()), Ok(1));
+    }
+    #[test]
+    fn overflow() {
+        // A chain of 70 diamonds has 2^70 paths from end to end
+        let mut edges = Vec::new();
+        let names:Vec<String> = (0..=70).map(|i| format!("n{}", i)).collect();
+        let (lefts, rights):(Vec<String>, Vec<String>) = (0..70).map(|i| (format!("l{}", i), format!("r{}", i))).unzip();
+        for i in 0..70 {
+            edges.extend([(names[i].as_str(), lefts[i].as_str()), (names[i].as_str(), rights[i].as_str())]);
+            edges.extend([(lefts[i].as_str(), names[i + 1].as_str()), (rights[i].as_str(), names[i + 1].as_str())]);
+        }
+        let g = graph(&edges);
+        let (start, end) = (g.id("n0").unwrap(), g.id("n70").unwrap());
+        assert_eq!(g.count_paths::<i64>(start, end), Err(Error::Overflow("the number of paths from n0 to n70 does not fit in i64".to_string())));
+        assert_eq!(g.count_paths::<BigInt>(start, end), Ok(BigInt::from(2).pow(70)));
+        assert_eq!(g.count_paths_via::<i128>(start, end, &[g.id("n35").unwrap()]), Ok(1 << 70));
+        assert!(g.count_paths_via::<i64>(start, end, &[g.id("n35").unwrap()]).is_err());
+    }
+}
@@ -0,0 +1,240 @@
+use super::{adjacent4, adjacent8, Vec2};
+use crate::error::Error;
+
+/// Dense 2D grid of `T` stored in row-major order and indexed by `Vec2`
+/// positions, with x increasing to the right and y increasing downwards
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells:Vec<T>,
+    width:usize,
+    height:usize
+}
+impl<T> Grid<T> {
+    /// Construct a grid from `rows`, which must all have the same length
+    pub fn from_rows(rows:Vec<Vec<T>>) -> Result<Grid<T>, Error> {
+        let height = rows.len();
+        let width = if height > 0 { rows[0].len() } else { 0 };
+        let mut cells = Vec::with_capacity(width * height);
+        for (y, row) in rows.into_iter().enumerate() {
+            if row.len() != width {
+                return Err(Error::parse_at(y, row.len().min(width), &format!("Irregular grid: expecting width {}, found {}", width, row.len())));
+            }
+            cells.extend(row);
+        }
+        return Ok(Grid { cells, width, height });
+    }
+    pub fn width(&self) -> usize {
+        return self.width;
+    }
+    pub fn height(&self) -> usize {
+        return self.height;
+    }
+    /// Tests if `pos` is within the grid
+    pub fn in_bounds(&self, pos:Vec2) -> bool {
+        return pos.in_bounds(self.width, self.height);
+    }
+    /// The cell at `pos`, or `None` if it is outside the grid
+    pub fn get(&self, pos:Vec2) -> Option<&T> {
+        if !self.in_bounds(pos) {
+            return None;
+        }
+        return Some(&self.cells[pos.y as usize * self.width + pos.x as usize]);
+    }
+    /// Mutable reference to the cell at `pos`, or `None` if it is outside the
+    /// grid
+    pub fn get_mut(&mut self, pos:Vec2) -> Option<&mut T> {
+        if !self.in_bounds(pos) {
+            return None;
+        }
+        return Some(&mut self.cells[pos.y as usize * self.width + pos.x as usize]);
+    }
+    /// All positions in the grid in row-major order
+    pub fn positions(&self) -> impl Iterator<Item=Vec2> + use<T> {
+        let width = self.width;
+        return (0..self.width * self.height).map(move |i| Vec2::newu(i % width, i / width));
+    }
+    /// Orthogonally adjacent positions to `pos` (N,E,S,W) that are within the
+    /// grid
+    pub fn neighbors4(&self, pos:Vec2) -> impl Iterator<Item=Vec2> + '_ {
+        return adjacent4().into_iter().map(move |dir| pos + dir).filter(|p| self.in_bounds(*p));
+    }
+    /// Adjacent positions to `pos` including diagonals (N,NE,E,SE,S,SW,W,NW)
+    /// that are within the grid
+    pub fn neighbors8(&self, pos:Vec2) -> impl Iterator<Item=Vec2> + '_ {
+        return adjacent8().into_iter().map(move |dir| pos + dir).filter(|p| self.in_bounds(*p));
+    }
+    /// The cells of row `y`, or `None` if it is outside the grid
+    pub fn row(&self, y:usize) -> Option<&[T]> {
+        if y >= self.height {
+            return None;
+        }
+        return Some(&self.cells[y * self.width..(y + 1) * self.width]);
+    }
+    /// Iterate over the rows of the grid from top to bottom
+    pub fn rows(&self) -> impl Iterator<Item=&[T]> {
+        return (0..self.height).filter_map(|y| self.row(y));
+    }
+    /// The cells of column `x`, from top to bottom, or `None` if it is
+    /// outside the grid
+    pub fn column(&self, x:usize) -> Option<impl Iterator<Item=&T>> {
+        if x >= self.width {
+            return None;
+        }
+        return Some(self.cells[x..].iter().step_by(self.width).take(self.height));
+    }
+    /// Position of the first cell in row-major order matching `predicate`
+    pub fn position(&self, predicate:impl Fn(&T) -> bool) -> Option<Vec2> {
+        let index = self.cells.iter().position(predicate)?;
+        return Some(Vec2::newu(index % self.width, index / self.width));
+    }
+    /// Construct a grid of the same dimensions by applying `f` to each cell
+    pub fn map<U>(&self, f:impl Fn(&T) -> U) -> Grid<U> {
+        return Grid { cells:self.cells.iter().map(f).collect(), width:self.width, height:self.height };
+    }
+}
+impl<T: Clone> Grid<T> {
+    /// Construct a `width` x `height` grid with every cell set to `fill`
+    pub fn new(width:usize, height:usize, fill:T) -> Grid<T> {
+        return Grid { cells:vec![fill; width * height], width, height };
+    }
+    /// Construct a new grid with each cell of this grid moved to the
+    /// position given by `to` for its position, with the new `width` and
+    /// `height`
+    fn rearranged(&self, width:usize, height:usize, to:impl Fn(usize, usize) -> (usize, usize)) -> Grid<T> {
+        let mut cells = self.cells.clone();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (to_x, to_y) = to(x, y);
+                cells[to_y * width + to_x] = self.cells[y * self.width + x].clone();
+            }
+        }
+        return Grid { cells, width, height };
+    }
+    /// Swap rows and columns
+    pub fn transpose(&self) -> Grid<T> {
+        return self.rearranged(self.height, self.width, |x, y| (y, x));
+    }
+    /// Rotate a quarter turn clockwise
+    pub fn rotate_clockwise(&self) -> Grid<T> {
+        let height = self.height;
+        return self.rearranged(self.height, self.width, |x, y| (height - 1 - y, x));
+    }
+    /// Rotate a quarter turn counterclockwise
+    pub fn rotate_counterclockwise(&self) -> Grid<T> {
+        let width = self.width;
+        return self.rearranged(self.height, self.width, |x, y| (y, width - 1 - x));
+    }
+    /// Mirror left to right
+    pub fn flip_horizontal(&self) -> Grid<T> {
+        let width = self.width;
+        return self.rearranged(self.width, self.height, |x, y| (width - 1 - x, y));
+    }
+    /// Mirror top to bottom
+    pub fn flip_vertical(&self) -> Grid<T> {
+        let height = self.height;
+        return self.rearranged(self.width, self.height, |x, y| (x, height - 1 - y));
+    }
+}
+impl<T: PartialEq> Grid<T> {
+    /// Position of the first cell in row-major order equal to `value`
+    pub fn find(&self, value:&T) -> Option<Vec2> {
+        return self.position(|c| c == value);
+    }
+    /// Positions of all cells equal to `value`, in row-major order
+    pub fn find_all(&self, value:&T) -> Vec<Vec2> {
+        return self.positions().filter(|p| self[*p] == *value).collect();
+    }
+}
+impl Grid<char> {
+    /// Read a grid of characters from `lines`, which must all have the same
+    /// length
+    pub fn from_lines(lines:&Vec<String>) -> Result<Grid<char>, Error> {
+        if lines.is_empty() {
+            return Err(Error::Input("lines are empty".to_string()));
+        }
+        return Grid::from_rows(lines.iter().map(|line| line.chars().collect()).collect());
+    }
+    /// Read a grid of characters from `lines`, padding lines shorter than the
+    /// longest with `fill`
+    pub fn from_lines_padded(lines:&Vec<String>, fill:char) -> Grid<char> {
+        let width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+        let mut grid = Grid::new(width, lines.len(), fill);
+        for y in 0..lines.len() {
+            for (x, c) in lines[y].chars().enumerate() {
+                grid[Vec2::newu(x, y)] = c;
+            }
+        }
+        return grid;
+    }
+}
+impl<T> std::ops::Index<Vec2> for Grid<T> {
+    type Output = T;
+    fn index(&self, pos:Vec2) -> &T {
+        match self.get(pos) {
+            Some(cell) => return cell,
+            None => panic!("Position {} outside {}x{} grid", pos, self.width, self.height)
+        }
+    }
+}
+impl<T> std::ops::IndexMut<Vec2> for Grid<T> {
+    fn index_mut(&mut self, pos:Vec2) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        match self.get_mut(pos) {
+            Some(cell) => return cell,
+            None => panic!("Position {} outside {}x{} grid", pos, width, height)
+        }
+    }
+}
+impl<T: std::fmt::Display> std::fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut  std::fmt::Formatter) ->  std::fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(s:&str) -> Grid<char> {
+        return Grid::from_lines(&s.lines().map(|l| l.to_string()).collect()).unwrap();
+    }
+    #[test]
+    fn indexing_and_views() {
+        let g = grid("abc\ndef");
+        assert_eq!((g.width(), g.height()), (3, 2));
+        assert_eq!(g[Vec2::new(2, 1)], 'f');
+        assert_eq!(g.get(Vec2::new(3, 0)), None);
+        assert_eq!(g.get(Vec2::new(0, -1)), None);
+        assert_eq!(g.row(1), Some(&['d', 'e', 'f'][..]));
+        assert_eq!(g.row(2), None);
+        assert_eq!(g.column(1).unwrap().collect::<String>(), "be");
+        assert!(g.column(3).is_none());
+        assert_eq!(g.find(&'e'), Some(Vec2::new(1, 1)));
+        assert_eq!(g.find(&'z'), None);
+        assert_eq!(g.neighbors4(Vec2::new(0, 0)).collect::<Vec<_>>(), vec![Vec2::new(1, 0), Vec2::new(0, 1)]);
+        assert_eq!(g.neighbors8(Vec2::new(1, 0)).count(), 5);
+        assert_eq!(g.to_string(), "abc\ndef\n");
+    }
+    #[test]
+    fn transforms() {
+        let g = grid("abc\ndef");
+        assert_eq!(g.transpose(), grid("ad\nbe\ncf"));
+        assert_eq!(g.rotate_clockwise(), grid("da\neb\nfc"));
+        assert_eq!(g.rotate_counterclockwise(), grid("cf\nbe\nad"));
+        assert_eq!(g.flip_horizontal(), grid("cba\nfed"));
+        assert_eq!(g.flip_vertical(), grid("def\nabc"));
+        assert_eq!(g.rotate_clockwise().rotate_counterclockwise(), g);
+    }
+    #[test]
+    fn irregular_lines() {
+        let lines = vec!["ab".to_string(), "c".to_string()];
+        assert!(matches!(Grid::from_lines(&lines), Err(Error::Parse { line:2, column:2, .. })));
+        assert_eq!(Grid::from_lines_padded(&lines, '.'), grid("ab\nc."));
+    }
+}
@@ -0,0 +1,273 @@
+use super::{Vec2, Vec3};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// A point with integer coordinates that can be stored in a `KdTree`
+pub trait Point: Copy {
+    /// Number of coordinate axes
+    const DIMS:usize;
+    /// The coordinate along `axis`, which is less than `DIMS`
+    fn coord(&self, axis:usize) -> isize;
+    /// Squared euclidean distance to `other`
+    fn dist_squared(&self, other:&Self) -> isize {
+        let mut dist = 0;
+        for axis in 0..Self::DIMS {
+            let diff = self.coord(axis) - other.coord(axis);
+            dist += diff * diff;
+        }
+        return dist;
+    }
+}
+impl Point for Vec2 {
+    const DIMS:usize = 2;
+    fn coord(&self, axis:usize) -> isize {
+        return if axis == 0 { self.x } else { self.y };
+    }
+}
+impl Point for Vec3 {
+    const DIMS:usize = 3;
+    fn coord(&self, axis:usize) -> isize {
+        match axis {
+            0 => return self.x,
+            1 => return self.y,
+            _ => return self.z
+        }
+    }
+}
+
+/// Static k-d tree over a set of points. Queries return points as
+/// (squared distance, point index) pairs, where the index is the position of
+/// the point in the list the tree was built from. Ties in distance are broken
+/// by point index, so results are deterministic.
+pub struct KdTree<P: Point> {
+    points:Vec<P>,
+    /// Point indices arranged as an implicit balanced tree. The subtree over
+    /// `order[lo..hi]` has its splitting point at the midpoint, with points
+    /// on the lower side of the split before it and the upper side after it.
+    order:Vec<usize>
+}
+impl<P: Point> KdTree<P> {
+    /// Build the tree over `points`
+    pub fn new(points:&Vec<P>) -> KdTree<P> {
+        let mut tree = KdTree { points:points.clone(), order:(0..points.len()).collect() };
+        tree.build(0, points.len(), 0);
+        return tree;
+    }
+    fn build(&mut self, lo:usize, hi:usize, depth:usize) {
+        if hi - lo < 2 {
+            return;
+        }
+        let axis = depth % P::DIMS;
+        let mid = (lo + hi) / 2;
+        let points = &self.points;
+        self.order[lo..hi].select_nth_unstable_by_key(mid - lo, |&i| points[i].coord(axis));
+        self.build(lo, mid, depth + 1);
+        self.build(mid + 1, hi, depth + 1);
+    }
+    /// Number of points in the tree
+    pub fn len(&self) -> usize {
+        return self.points.len();
+    }
+    pub fn is_empty(&self) -> bool {
+        return self.points.is_empty();
+    }
+    /// The point with the given `index`
+    pub fn point(&self, index:usize) -> P {
+        return self.points[index];
+    }
+    /// The `k` points nearest to `target`, nearest first
+    pub fn nearest(&self, target:&P, k:usize) -> Vec<(isize, usize)> {
+        let mut best = BinaryHeap::new();
+        if k > 0 {
+            self.nearest_search((0, self.points.len()), 0, target, k, None, &mut best);
+        }
+        return best.into_sorted_vec();
+    }
+    /// The `k` points nearest to the point with the given `index`, excluding
+    /// the point itself, nearest first
+    pub fn neighbors(&self, index:usize, k:usize) -> Vec<(isize, usize)> {
+        let mut best = BinaryHeap::new();
+        if k > 0 {
+            self.nearest_search((0, self.points.len()), 0, &self.points[index], k, Some(index), &mut best);
+        }
+        return best.into_sorted_vec();
+    }
+    /// Depth first search of the subtree over `order[lo..hi]` keeping the `k`
+    /// nearest points found so far in the max-heap `best`, skipping the 
+    /// `exclude` index
+    fn nearest_search(&self, (lo, hi):(usize, usize), depth:usize, target:&P, k:usize, exclude:Option<usize>, best:&mut BinaryHeap<(isize, usize)>) {
+        if lo >= hi {
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        let index = self.order[mid];
+        let point = &self.points[index];
+        if exclude != Some(index) {
+            let candidate = (point.dist_squared(target), index);
+            if best.len() < k {
+                best.push(candidate);
+            }
+            else if candidate < *best.peek().unwrap() {
+                best.pop();
+                best.push(candidate);
+            }
+        }
+        // Search the side of the split containing the target first, then the
+        // other side only if it could hold a point at least as near as the
+        // furthest kept so far
+        let axis = depth % P::DIMS;
+        let diff = target.coord(axis) - point.coord(axis);
+        let (near, far) = if diff < 0 { ((lo, mid), (mid + 1, hi)) } else { ((mid + 1, hi), (lo, mid)) };
+        self.nearest_search(near, depth + 1, target, k, exclude, best);
+        if best.len() < k || diff * diff <= best.peek().unwrap().0 {
+            self.nearest_search(far, depth + 1, target, k, exclude, best);
+        }
+    }
+    /// All points within squared distance `radius_squared` of `target`
+    /// (inclusive), nearest first
+    pub fn within(&self, target:&P, radius_squared:isize) -> Vec<(isize, usize)> {
+        let mut found = Vec::new();
+        self.within_search((0, self.points.len()), 0, target, radius_squared, &mut found);
+        found.sort();
+        return found;
+    }
+    fn within_search(&self, (lo, hi):(usize, usize), depth:usize, target:&P, radius_squared:isize, found:&mut Vec<(isize, usize)>) {
+        if lo >= hi {
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        let index = self.order[mid];
+        let point = &self.points[index];
+        let dist = point.dist_squared(target);
+        if dist <= radius_squared {
+            found.push((dist, index));
+        }
+        let axis = depth % P::DIMS;
+        let diff = target.coord(axis) - point.coord(axis);
+        if diff <= 0 || diff * diff <= radius_squared {
+            self.within_search((lo, mid), depth + 1, target, radius_squared, found);
+        }
+        if diff >= 0 || diff * diff <= radius_squared {
+            self.within_search((mid + 1, hi), depth + 1, target, radius_squared, found);
+        }
+    }
+    /// Iterate over every pair of points as (squared distance, index a,
+    /// index b) with a < b, in increasing order of distance then index. Pairs
+    /// are found lazily, so only as many neighbours are queried as the
+    /// iteration consumes.
+    pub fn closest_pairs(&self) -> ClosestPairs<'_, P> {
+        let n = self.points.len();
+        let mut pairs = ClosestPairs {
+            tree:self,
+            candidates:BinaryHeap::new(),
+            neighbors:vec![Vec::new(); n],
+            next:vec![0; n]
+        };
+        for i in 0..n {
+            pairs.advance(i);
+        }
+        return pairs;
+    }
+}
+
+/// Number of neighbours first queried for each point by `ClosestPairs`
+const INITIAL_NEIGHBORS:usize = 8;
+
+/// Iterator over the pairs of points of a `KdTree` in increasing order of
+/// distance. See `KdTree::closest_pairs`.
+pub struct ClosestPairs<'a, P: Point> {
+    tree:&'a KdTree<P>,
+    /// The nearest pair not yet returned for each point that has one
+    candidates:BinaryHeap<Reverse<(isize, usize, usize)>>,
+    /// The nearest neighbours queried so far for each point, nearest first
+    neighbors:Vec<Vec<(isize, usize)>>,
+    /// Position in `neighbors` of the next neighbour to consider per point
+    next:Vec<usize>
+}
+impl<'a, P: Point> ClosestPairs<'a, P> {
+    /// Add the next nearest pair for point `a` with a higher-indexed point to
+    /// the candidates, querying more neighbours if those found are used up
+    fn advance(&mut self, a:usize) {
+        let n = self.tree.len();
+        loop {
+            while self.next[a] < self.neighbors[a].len() {
+                let (dist, b) = self.neighbors[a][self.next[a]];
+                self.next[a] += 1;
+                // Each pair is returned from its lower-indexed point only
+                if b > a {
+                    self.candidates.push(Reverse((dist, a, b)));
+                    return;
+                }
+            }
+            let queried = self.neighbors[a].len();
+            if queried + 1 >= n {
+                return;
+            }
+            // Query twice as many neighbours. The nearest neighbours are
+            // ordered by distance then index, so the ones already considered
+            // are a prefix of the new query.
+            let k = (queried * 2).max(INITIAL_NEIGHBORS).min(n - 1);
+            self.neighbors[a] = self.tree.neighbors(a, k);
+        }
+    }
+}
+impl<'a, P: Point> Iterator for ClosestPairs<'a, P> {
+    type Item = (isize, usize, usize);
+    fn next(&mut self) -> Option<(isize, usize, usize)> {
+        let Reverse(pair) = self.candidates.pop()?;
+        self.advance(pair.1);
+        return Some(pair);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pseudo-random points from a linear congruential generator, with a
+    /// small coordinate range so there are plenty of ties
+    fn points(n:usize) -> Vec<Vec3> {
+        let mut state:u64 = 12345;
+        let mut next = || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            return ((state >> 33) % 20) as isize - 10;
+        };
+        return (0..n).map(|_| Vec3::new(next(), next(), next())).collect();
+    }
+    fn brute_force(points:&Vec<Vec3>, target:&Vec3) -> Vec<(isize, usize)> {
+        let mut dists:Vec<(isize, usize)> = points.iter().enumerate().map(|(i, p)| (p.dist_squared(target), i)).collect();
+        dists.sort();
+        return dists;
+    }
+    #[test]
+    fn nearest_and_within() {
+        let points = points(200);
+        let tree = KdTree::new(&points);
+        let target = Vec3::new(1, -2, 3);
+        let expected = brute_force(&points, &target);
+        assert_eq!(tree.nearest(&target, 15), expected[..15].to_vec());
+        assert_eq!(tree.within(&target, 20), expected.iter().filter(|d| d.0 <= 20).cloned().collect::<Vec<_>>());
+        let neighbors:Vec<(isize, usize)> = brute_force(&points, &points[7]).into_iter().filter(|d| d.1 != 7).take(10).collect();
+        assert_eq!(tree.neighbors(7, 10), neighbors);
+    }
+    #[test]
+    fn closest_pairs_in_order() {
+        let points = points(60);
+        let tree = KdTree::new(&points);
+        let mut expected = Vec::new();
+        for i in 0..points.len() {
+            for j in i+1..points.len() {
+                expected.push((points[i].dist_squared(&points[j]), i, j));
+            }
+        }
+        expected.sort();
+        assert_eq!(tree.closest_pairs().collect::<Vec<_>>(), expected);
+    }
+    #[test]
+    fn vec2_points() {
+        let points = vec![Vec2::new(0, 0), Vec2::new(5, 5), Vec2::new(1, 0), Vec2::new(0, 2)];
+        let tree = KdTree::new(&points);
+        assert_eq!(tree.nearest(&Vec2::new(0, 0), 2), vec![(0, 0), (1, 2)]);
+        assert_eq!(tree.closest_pairs().next(), Some((1, 0, 2)));
+    }
+}
@@ -0,0 +1,506 @@
+use super::{Integer, Rational};
+use crate::error::Error;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Relation between the two sides of a linear constraint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Relation {
+    LessEq,
+    Equal,
+    GreaterEq
+}
+
+/// Outcome of solving a `LinearProgram`
+#[derive(Debug, Clone, PartialEq)]
+pub enum LpSolution<T> {
+    /// An optimal assignment of the variables and its objective value
+    Optimal { x:Vec<T>, value:T },
+    /// No assignment satisfies the constraints
+    Infeasible,
+    /// The objective can be made arbitrarily small
+    Unbounded
+}
+
+/// Linear program: minimise `objective . x` subject to linear constraints and
+/// `x >= 0`, with exact rational arithmetic over the integer type `I`. Solving
+/// reports arithmetic that overflows `I` as `Error::Overflow`.
+#[derive(Debug, Clone)]
+pub struct LinearProgram<I: Integer> {
+    objective:Vec<Rational<I>>,
+    constraints:Vec<(Vec<Rational<I>>, Relation, Rational<I>)>
+}
+
+/// Simplex tableau of equality constraints over non-negative variables, with
+/// one basic variable per row and a row of reduced costs kept up to date
+/// through pivots, so a solved tableau can be tightened and re-solved
+#[derive(Debug, Clone)]
+struct Tableau<I: Integer> {
+    /// Coefficients of each row followed by its right hand side
+    rows:Vec<Vec<Rational<I>>>,
+    /// The basic variable of each row
+    basis:Vec<usize>,
+    /// Reduced cost of each column followed by minus the objective value
+    costs:Vec<Rational<I>>
+}
+impl<I: Integer> Tableau<I> {
+    fn cols(&self) -> usize {
+        return self.costs.len() - 1;
+    }
+    fn rhs(&self, row:usize) -> &Rational<I> {
+        return self.rows[row].last().unwrap();
+    }
+    /// Objective value of the basic solution, or `None` on overflow
+    fn value(&self) -> Option<Rational<I>> {
+        return self.costs.last().unwrap().checked_neg();
+    }
+    /// Value of each of the first `cols` variables in the basic solution
+    fn solution(&self, cols:usize) -> Vec<Rational<I>> {
+        let mut x = vec![Rational::zero(); cols];
+        for r in 0..self.rows.len() {
+            if self.basis[r] < cols {
+                x[self.basis[r]] = self.rhs(r).clone();
+            }
+        }
+        return x;
+    }
+    /// Subtract `factor` times `pivot_row` from `row`, skipping the zero
+    /// entries of the pivot row. Returns `None` on overflow.
+    fn eliminate(row:&mut [Rational<I>], pivot_row:&[Rational<I>], factor:&Rational<I>) -> Option<()> {
+        for c in 0..pivot_row.len() {
+            if pivot_row[c] != Rational::zero() {
+                row[c] = row[c].checked_sub(&factor.checked_mul(&pivot_row[c])?)?;
+            }
+        }
+        return Some(());
+    }
+    fn pivot(&mut self, row:usize, col:usize) -> Option<()> {
+        let pivot = self.rows[row][col].clone();
+        for entry in self.rows[row].iter_mut() {
+            if *entry != Rational::zero() {
+                *entry = entry.checked_div(&pivot)?;
+            }
+        }
+        let pivot_row = self.rows[row].clone();
+        for r in 0..self.rows.len() {
+            if r != row && self.rows[r][col] != Rational::zero() {
+                let factor = self.rows[r][col].clone();
+                Tableau::eliminate(&mut self.rows[r], &pivot_row, &factor)?;
+            }
+        }
+        if self.costs[col] != Rational::zero() {
+            let factor = self.costs[col].clone();
+            Tableau::eliminate(&mut self.costs, &pivot_row, &factor)?;
+        }
+        self.basis[row] = col;
+        return Some(());
+    }
+    /// Replace the objective with `cost`, which has an entry for each column,
+    /// pricing out the basic variables
+    fn set_costs(&mut self, cost:&[Rational<I>]) -> Option<()> {
+        self.costs = cost.to_vec();
+        self.costs.push(Rational::zero());
+        for r in 0..self.rows.len() {
+            let factor = self.costs[self.basis[r]].clone();
+            if factor != Rational::zero() {
+                let row = self.rows[r].clone();
+                Tableau::eliminate(&mut self.costs, &row, &factor)?;
+            }
+        }
+        return Some(());
+    }
+    /// Primal simplex: pivot from a feasible basis until no reduced cost is
+    /// negative. Columns enter by the most negative reduced cost, switching
+    /// to Bland's rule (lowest index first) after a run of degenerate pivots
+    /// so the method can't cycle. Returns false if the objective is
+    /// unbounded, or `None` on overflow.
+    fn primal(&mut self) -> Option<bool> {
+        let mut degenerate = 0;
+        loop {
+            let bland = degenerate > self.cols();
+            let mut entering:Option<usize> = None;
+            for c in 0..self.cols() {
+                if self.costs[c] < Rational::zero() && entering.is_none_or(|e| !bland && self.costs[c] < self.costs[e]) {
+                    entering = Some(c);
+                }
+            }
+            let Some(col) = entering else {
+                return Some(true);
+            };
+            // The leaving row has the smallest ratio of right hand side to
+            // positive coefficient, with ties to the lowest basic variable
+            let mut leaving:Option<(Rational<I>, usize)> = None;
+            for r in 0..self.rows.len() {
+                if self.rows[r][col] > Rational::zero() {
+                    let ratio = self.rhs(r).checked_div(&self.rows[r][col])?;
+                    if leaving.as_ref().is_none_or(|(best, row)| (&ratio, self.basis[r]) < (best, self.basis[*row])) {
+                        leaving = Some((ratio, r));
+                    }
+                }
+            }
+            let Some((ratio, row)) = leaving else {
+                return Some(false);
+            };
+            degenerate = if ratio == Rational::zero() { degenerate + 1 } else { 0 };
+            self.pivot(row, col)?;
+        }
+    }
+    /// Dual simplex: pivot from a basis with no negative reduced cost until
+    /// no right hand side is negative, with the same anti-cycling rule as
+    /// `primal`. Returns false if the constraints are infeasible, or `None`
+    /// on overflow.
+    fn dual(&mut self) -> Option<bool> {
+        let mut degenerate = 0;
+        loop {
+            let bland = degenerate > self.cols();
+            let mut leaving:Option<usize> = None;
+            for r in 0..self.rows.len() {
+                if *self.rhs(r) < Rational::zero() && leaving.is_none_or(|l| if bland { self.basis[r] < self.basis[l] } else { self.rhs(r) < self.rhs(l) }) {
+                    leaving = Some(r);
+                }
+            }
+            let Some(row) = leaving else {
+                return Some(true);
+            };
+            // The entering column keeps the reduced costs non-negative: the
+            // smallest ratio of reduced cost to negated negative coefficient,
+            // with ties to the lowest column
+            let mut entering:Option<(Rational<I>, usize)> = None;
+            for c in 0..self.cols() {
+                if self.rows[row][c] < Rational::zero() {
+                    let ratio = self.costs[c].checked_div(&self.rows[row][c].checked_neg()?)?;
+                    if entering.as_ref().is_none_or(|(best, _)| ratio < *best) {
+                        entering = Some((ratio, c));
+                    }
+                }
+            }
+            let Some((ratio, col)) = entering else {
+                return Some(false);
+            };
+            degenerate = if ratio == Rational::zero() { degenerate + 1 } else { 0 };
+            self.pivot(row, col)?;
+        }
+    }
+    /// Add the constraint `x[var] <= bound` or `x[var] >= bound` with a new
+    /// slack column, which starts basic in the new row, then restore
+    /// optimality with the dual simplex. Returns false if the tightened
+    /// tableau is infeasible, or `None` on overflow.
+    fn tighten(&mut self, var:usize, relation:Relation, bound:Rational<I>) -> Option<bool> {
+        let cols = self.cols();
+        for row in self.rows.iter_mut() {
+            row.insert(cols, Rational::zero());
+        }
+        self.costs.insert(cols, Rational::zero());
+        // x + s = bound, or -x + s = -bound, rewritten in terms of the
+        // non-basic variables by eliminating the basic ones
+        let one = Rational::new_int(I::one());
+        let mut row = vec![Rational::zero(); cols + 2];
+        row[cols] = one.clone();
+        if relation == Relation::LessEq {
+            row[var] = one;
+            row[cols + 1] = bound;
+        }
+        else {
+            row[var] = one.checked_neg()?;
+            row[cols + 1] = bound.checked_neg()?;
+        }
+        for r in 0..self.rows.len() {
+            let factor = row[self.basis[r]].clone();
+            if factor != Rational::zero() {
+                Tableau::eliminate(&mut row, &self.rows[r], &factor)?;
+            }
+        }
+        self.rows.push(row);
+        self.basis.push(cols);
+        return self.dual();
+    }
+}
+
+impl<I: Integer> LinearProgram<I> {
+    /// A program minimising `objective . x`, which has a coefficient for each
+    /// variable, with no constraints other than `x >= 0`
+    pub fn new(objective:Vec<Rational<I>>) -> LinearProgram<I> {
+        return LinearProgram { objective, constraints:Vec::new() };
+    }
+    /// Number of variables
+    pub fn len(&self) -> usize {
+        return self.objective.len();
+    }
+    pub fn is_empty(&self) -> bool {
+        return self.objective.is_empty();
+    }
+    /// Add the constraint `coefficients . x relation rhs`
+    pub fn constrain(&mut self, coefficients:Vec<Rational<I>>, relation:Relation, rhs:Rational<I>) {
+        assert_eq!(coefficients.len(), self.len(), "Constraint has {} coefficients for {} variables", coefficients.len(), self.len());
+        self.constraints.push((coefficients, relation, rhs));
+    }
+    /// The error for a program whose arithmetic doesn't fit in `I`
+    fn overflow() -> Error {
+        return Error::Overflow(format!("solving the linear program does not fit in {}", std::any::type_name::<I>()));
+    }
+    /// The optimal tableau of the linear relaxation, found with the two-phase
+    /// simplex method, or why there isn't one. Returns `None` on overflow.
+    fn optimal_tableau(&self) -> Option<Result<Tableau<I>, LpSolution<Rational<I>>>> {
+        let n = self.len();
+        let m = self.constraints.len();
+        // Columns are the variables, then a slack or surplus variable for
+        // each inequality, then an artificial variable for each row
+        let inequalities = self.constraints.iter().filter(|c| c.1 != Relation::Equal).count();
+        let artificial = n + inequalities;
+        let cols = artificial + m;
+        let mut tableau = Tableau { rows:Vec::new(), basis:Vec::new(), costs:Vec::new() };
+        let mut slack = n;
+        for (i, (coefficients, relation, rhs)) in self.constraints.iter().enumerate() {
+            let mut row = vec![Rational::zero(); cols + 1];
+            row[..n].clone_from_slice(coefficients);
+            match relation {
+                Relation::LessEq => row[slack] = Rational::new_int(I::one()),
+                Relation::GreaterEq => row[slack] = Rational::new_int(I::one()).checked_neg()?,
+                Relation::Equal => {}
+            }
+            if *relation != Relation::Equal {
+                slack += 1;
+            }
+            row[cols] = rhs.clone();
+            // Keep the right hand side non-negative so the artificial
+            // variables start feasible
+            if *rhs < Rational::zero() {
+                for entry in row.iter_mut() {
+                    *entry = entry.checked_neg()?;
+                }
+            }
+            row[artificial + i] = Rational::new_int(I::one());
+            tableau.rows.push(row);
+            tableau.basis.push(artificial + i);
+        }
+        // Phase 1: minimise the sum of the artificial variables to find a
+        // feasible basis
+        let mut phase1 = vec![Rational::zero(); cols];
+        for i in 0..m {
+            phase1[artificial + i] = Rational::new_int(I::one());
+        }
+        tableau.set_costs(&phase1)?;
+        tableau.primal()?;
+        if tableau.value()? != Rational::zero() {
+            return Some(Err(LpSolution::Infeasible));
+        }
+        // Pivot any artificial variables left in the basis (at zero) out, or
+        // drop their rows if they are redundant, then drop their columns
+        let mut r = 0;
+        while r < tableau.rows.len() {
+            if tableau.basis[r] >= artificial {
+                match (0..artificial).find(|&c| tableau.rows[r][c] != Rational::zero()) {
+                    Some(c) => tableau.pivot(r, c)?,
+                    None => {
+                        tableau.rows.remove(r);
+                        tableau.basis.remove(r);
+                        continue;
+                    }
+                }
+            }
+            r += 1;
+        }
+        for row in tableau.rows.iter_mut() {
+            row.drain(artificial..cols);
+        }
+        // Phase 2: minimise the objective
+        let mut cost = vec![Rational::zero(); artificial];
+        cost[..n].clone_from_slice(&self.objective);
+        tableau.set_costs(&cost)?;
+        if !tableau.primal()? {
+            return Some(Err(LpSolution::Unbounded));
+        }
+        return Some(Ok(tableau));
+    }
+    /// Solve the linear relaxation exactly with the two-phase simplex method.
+    /// Returns `Error::Overflow` if the arithmetic doesn't fit in `I`.
+    pub fn solve(&self) -> Result<LpSolution<Rational<I>>, Error> {
+        match self.optimal_tableau().ok_or_else(LinearProgram::<I>::overflow)? {
+            Ok(tableau) => return Ok(LpSolution::Optimal { x:tableau.solution(self.len()), value:tableau.value().ok_or_else(LinearProgram::<I>::overflow)? }),
+            Err(outcome) => return Ok(outcome)
+        }
+    }
+    /// Solve with every variable restricted to integers, by branch and bound
+    /// on the linear relaxation. Each branch adds a bound on one variable to
+    /// its parent's optimal tableau and re-solves it with the dual simplex
+    /// method, and the branch with the lowest bound is explored next. The
+    /// search only discards a branch once its relaxation proves it can't beat
+    /// the best solution found, so the result is optimal. Also returns the
+    /// number of branches explored. Returns `Error::Overflow` if the
+    /// arithmetic doesn't fit in `I`.
+    pub fn solve_integer(&self) -> Result<(LpSolution<Rational<I>>, usize), Error> {
+        return self.branch_and_bound().ok_or_else(LinearProgram::<I>::overflow);
+    }
+    /// The search behind `solve_integer`, returning `None` on overflow
+    fn branch_and_bound(&self) -> Option<(LpSolution<Rational<I>>, usize)> {
+        // A variable with the same coefficients as a cheaper one can always
+        // be moved onto that one, so only the cheapest of each set of
+        // identical columns is kept, which saves searching symmetric branches
+        let column = |j:usize| self.constraints.iter().map(|c| c.0[j].clone()).collect::<Vec<Rational<I>>>();
+        let mut kept:Vec<usize> = Vec::new();
+        for j in 0..self.len() {
+            match kept.iter().position(|&k| column(k) == column(j)) {
+                Some(i) if self.objective[j] < self.objective[kept[i]] => kept[i] = j,
+                Some(_) => {},
+                None => kept.push(j)
+            }
+        }
+        if kept.len() < self.len() {
+            let mut reduced = LinearProgram::new(kept.iter().map(|&j| self.objective[j].clone()).collect());
+            for (coefficients, relation, rhs) in &self.constraints {
+                reduced.constrain(kept.iter().map(|&j| coefficients[j].clone()).collect(), *relation, rhs.clone());
+            }
+            match reduced.branch_and_bound()? {
+                (LpSolution::Optimal { x, value }, nodes) => {
+                    let mut full = vec![Rational::zero(); self.len()];
+                    for i in 0..kept.len() {
+                        full[kept[i]] = x[i].clone();
+                    }
+                    return Some((LpSolution::Optimal { x:full, value }, nodes));
+                },
+                other => return Some(other)
+            }
+        }
+        let root = match self.optimal_tableau()? {
+            Ok(tableau) => tableau,
+            Err(outcome) => return Some((outcome, 1))
+        };
+        // With integer costs the objective of an integer solution is an
+        // integer, so a relaxation can be rounded up to a tighter bound
+        let integer_costs = self.objective.iter().all(|c| c.is_integer());
+        let half = Rational::checked_new(I::one(), I::from_i64(2))?;
+        let bound = |tableau:&Tableau<I>| -> Option<Rational<I>> {
+            let value = tableau.value()?;
+            return if integer_costs { Some(Rational::new_int(value.checked_ceil()?)) } else { Some(value) };
+        };
+        // The fractional part of a value, which shares its denominator
+        let fraction = |v:&Rational<I>| Rational { num:v.num.div_rem_floor(&v.denom).1, denom:v.denom.clone() };
+        let mut best:Option<(Vec<Rational<I>>, Rational<I>)> = None;
+        let mut nodes = 0;
+        // Open nodes are explored best bound first, then deepest first so
+        // ties dive towards an integer solution, then latest first. The
+        // tableaux are kept apart from the heap, which only holds their keys.
+        let mut tableaux:Vec<Option<Tableau<I>>> = Vec::new();
+        let mut open = BinaryHeap::new();
+        open.push(Reverse((bound(&root)?, Reverse(0), Reverse(0))));
+        tableaux.push(Some(root));
+        while let Some(Reverse((node_bound, Reverse(depth), Reverse(id)))) = open.pop() {
+            nodes += 1;
+            let tableau = tableaux[id].take().unwrap();
+            if best.as_ref().is_some_and(|(_, best_value)| node_bound >= *best_value) {
+                continue;
+            }
+            let x = tableau.solution(self.len());
+            // Branch on the most fractional variable, whose fractional part
+            // is nearest a half
+            let mut branch:Option<(Rational<I>, usize)> = None;
+            for j in 0..x.len() {
+                if !x[j].is_integer() {
+                    let distance = fraction(&x[j]).checked_sub(&half)?;
+                    let distance = if distance < Rational::zero() { distance.checked_neg()? } else { distance };
+                    if branch.as_ref().is_none_or(|(d, _)| distance < *d) {
+                        branch = Some((distance, j));
+                    }
+                }
+            }
+            let Some((_, j)) = branch else {
+                best = Some((x, tableau.value()?));
+                continue;
+            };
+            // Branch on x[j] <= floor(v) or x[j] >= ceil(v), adding the
+            // nearer side last so it is preferred on ties
+            let mut below = tableau.clone();
+            let below = below.tighten(j, Relation::LessEq, Rational::new_int(x[j].floor()))?.then_some(below);
+            let mut above = tableau;
+            let above = above.tighten(j, Relation::GreaterEq, Rational::new_int(x[j].checked_ceil()?))?.then_some(above);
+            let (far, near) = if fraction(&x[j]) < half { (above, below) } else { (below, above) };
+            for child in far.into_iter().chain(near) {
+                let child_bound = bound(&child)?;
+                if best.as_ref().is_none_or(|(_, best_value)| child_bound < *best_value) {
+                    open.push(Reverse((child_bound, Reverse(depth + 1), Reverse(tableaux.len()))));
+                    tableaux.push(Some(child));
+                }
+            }
+        }
+        match best {
+            Some((x, value)) => return Some((LpSolution::Optimal { x, value }, nodes)),
+            None => return Some((LpSolution::Infeasible, nodes))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::BigInt;
+
+    fn q(values:&[i64]) -> Vec<Rational> {
+        return values.iter().map(|&v| Rational::new_int(v)).collect();
+    }
+    #[test]
+    fn relaxation() {
+        // Maximise 3x + 2y, so minimise -3x - 2y, with x + y <= 4, x + 3y <= 6
+        // and x <= 3
+        let mut lp = LinearProgram::new(q(&[-3, -2]));
+        lp.constrain(q(&[1, 1]), Relation::LessEq, Rational::new_int(4));
+        lp.constrain(q(&[1, 3]), Relation::LessEq, Rational::new_int(6));
+        lp.constrain(q(&[1, 0]), Relation::LessEq, Rational::new_int(3));
+        assert_eq!(lp.solve().unwrap(), LpSolution::Optimal { x:q(&[3, 1]), value:Rational::new_int(-11) });
+        lp.constrain(q(&[1, 1]), Relation::GreaterEq, Rational::new_int(5));
+        assert_eq!(lp.solve().unwrap(), LpSolution::Infeasible);
+        let mut unbounded = LinearProgram::new(q(&[-1, 0]));
+        unbounded.constrain(q(&[1, -1]), Relation::Equal, Rational::new_int(2));
+        assert_eq!(unbounded.solve().unwrap(), LpSolution::Unbounded);
+    }
+    #[test]
+    fn fractional_relaxation() {
+        // x + y = 3/2 has no integer solution, and 2x + 2y = 3 is redundant
+        let mut lp = LinearProgram::new(q(&[1, 1]));
+        lp.constrain(vec![Rational::new_int(1), Rational::new_int(1)], Relation::Equal, Rational::new(3, 2));
+        lp.constrain(q(&[2, 2]), Relation::Equal, Rational::new_int(3));
+        assert!(matches!(lp.solve().unwrap(), LpSolution::Optimal { .. }));
+        assert_eq!(lp.solve_integer().unwrap().0, LpSolution::Infeasible);
+    }
+    #[test]
+    fn integer_program() {
+        // Minimise x + y + z with 2x + 3y = 12, y + 4z >= 5 over integers.
+        // The relaxation is fractional but the integer optimum is
+        // (0, 4, 1) at 5.
+        let mut lp = LinearProgram::new(q(&[1, 1, 1]));
+        lp.constrain(q(&[2, 3, 0]), Relation::Equal, Rational::new_int(12));
+        lp.constrain(q(&[0, 1, 4]), Relation::GreaterEq, Rational::new_int(5));
+        match lp.solve().unwrap() {
+            LpSolution::Optimal { value, .. } => assert!(value < 5),
+            other => panic!("expected an optimal relaxation, found {:?}", other)
+        }
+        let (solution, nodes) = lp.solve_integer().unwrap();
+        assert_eq!(solution, LpSolution::Optimal { x:q(&[0, 4, 1]), value:Rational::new_int(5) });
+        assert!(nodes > 1);
+    }
+    #[test]
+    fn duplicate_columns() {
+        // x and z have the same coefficients, so only the cheaper z is used
+        let mut lp = LinearProgram::new(q(&[3, 2, 2]));
+        lp.constrain(q(&[2, 1, 2]), Relation::Equal, Rational::new_int(7));
+        lp.constrain(q(&[1, 0, 1]), Relation::GreaterEq, Rational::new_int(2));
+        let (solution, _) = lp.solve_integer().unwrap();
+        assert_eq!(solution, LpSolution::Optimal { x:q(&[0, 1, 3]), value:Rational::new_int(8) });
+    }
+    #[test]
+    fn overflow() {
+        // The two rows have a determinant of about 2^42, and eliminating one
+        // with the other multiplies entries near 2^40 past i64, which BigInt
+        // solves exactly
+        let a = [(1 << 40) + 1, (1 << 40) + 3, (1 << 40) + 5, (1 << 40) + 9];
+        let mut lp = LinearProgram::new(q(&[1, 1]));
+        lp.constrain(q(&[a[0], a[1]]), Relation::Equal, Rational::new_int(a[0] + a[1]));
+        lp.constrain(q(&[a[2], a[3]]), Relation::Equal, Rational::new_int(a[2] + a[3]));
+        assert!(matches!(lp.solve(), Err(Error::Overflow(_))));
+        assert!(matches!(lp.solve_integer(), Err(Error::Overflow(_))));
+        let big = |values:&[i64]| values.iter().map(|&v| Rational::new_int(BigInt::from(v))).collect::<Vec<Rational<BigInt>>>();
+        let mut lp = LinearProgram::new(big(&[1, 1]));
+        lp.constrain(big(&[a[0], a[1]]), Relation::Equal, Rational::new_int(BigInt::from(a[0] + a[1])));
+        lp.constrain(big(&[a[2], a[3]]), Relation::Equal, Rational::new_int(BigInt::from(a[2] + a[3])));
+        assert_eq!(lp.solve_integer().unwrap().0, LpSolution::Optimal { x:big(&[1, 1]), value:Rational::new_int(BigInt::from(2)) });
+    }
+}
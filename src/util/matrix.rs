@@ -0,0 +1,430 @@
+use super::{BigInt, Integer, Rational};
+use crate::error::Error;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// Element type of a `Matrix`: a commutative ring with exact arithmetic
+pub trait Scalar: Clone + PartialEq + std::fmt::Debug + std::fmt::Display
+    + Add<Output=Self> + Sub<Output=Self> + Mul<Output=Self> + Neg<Output=Self> {
+    fn zero() -> Self;
+    fn one() -> Self;
+    fn is_zero(&self) -> bool {
+        return *self == Self::zero();
+    }
+}
+/// A `Scalar` where every non-zero element has a multiplicative inverse, so
+/// `Div` is exact. Row reduction and solving need a field.
+pub trait Field: Scalar + Div<Output=Self> {}
+
+macro_rules! integer_scalar {
+    ($($t:ty),*) => {
+        $(impl Scalar for $t {
+            fn zero() -> $t {
+                return <$t as Integer>::zero();
+            }
+            fn one() -> $t {
+                return <$t as Integer>::one();
+            }
+        })*
+    };
+}
+integer_scalar!(i64, i128, BigInt);
+impl<T: Integer> Scalar for Rational<T> {
+    fn zero() -> Rational<T> {
+        return Rational::zero();
+    }
+    fn one() -> Rational<T> {
+        return Rational::new_int(T::one());
+    }
+}
+impl<T: Integer> Field for Rational<T> {}
+
+/// Element of GF(2), the field of integers modulo 2, where addition is
+/// exclusive or and multiplication is and
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct Gf2(pub bool);
+impl std::fmt::Display for Gf2 {
+    fn fmt(&self, f: &mut  std::fmt::Formatter) ->  std::fmt::Result {
+        write!(f, "{}", if self.0 { 1 } else { 0 })
+    }
+}
+impl Add for Gf2 {
+    type Output = Gf2;
+    fn add(self, rhs:Gf2) -> Gf2 {
+        return Gf2(self.0 != rhs.0);
+    }
+}
+impl Sub for Gf2 {
+    type Output = Gf2;
+    fn sub(self, rhs:Gf2) -> Gf2 {
+        return Gf2(self.0 != rhs.0);
+    }
+}
+impl Mul for Gf2 {
+    type Output = Gf2;
+    fn mul(self, rhs:Gf2) -> Gf2 {
+        return Gf2(self.0 && rhs.0);
+    }
+}
+impl Div for Gf2 {
+    type Output = Gf2;
+    fn div(self, rhs:Gf2) -> Gf2 {
+        assert!(rhs.0, "Division of {} by zero", self);
+        return self;
+    }
+}
+impl Neg for Gf2 {
+    type Output = Gf2;
+    fn neg(self) -> Gf2 {
+        return self;
+    }
+}
+impl Scalar for Gf2 {
+    fn zero() -> Gf2 {
+        return Gf2(false);
+    }
+    fn one() -> Gf2 {
+        return Gf2(true);
+    }
+}
+impl Field for Gf2 {}
+
+/// The solutions of a linear system `Ax = b`
+#[derive(Debug, Clone, PartialEq)]
+pub enum SolutionSet<T> {
+    /// Exactly one solution
+    Unique(Vec<T>),
+    /// Every `particular + sum(t[i] * basis[i])`, where `basis[i]` is the
+    /// null space vector that is 1 in the free variable `free[i]` and 0 in
+    /// the other free variables, and `particular` has every free variable 0
+    Infinite { particular:Vec<T>, basis:Vec<Vec<T>>, free:Vec<usize> },
+    /// No solutions
+    Inconsistent
+}
+
+/// Dense matrix of `T` stored in row-major order
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix<T> {
+    cells:Vec<T>,
+    rows:usize,
+    cols:usize
+}
+impl<T: Scalar> Matrix<T> {
+    /// Construct a `rows` x `cols` matrix of zeros
+    pub fn new(rows:usize, cols:usize) -> Matrix<T> {
+        return Matrix { cells:vec![T::zero(); rows * cols], rows, cols };
+    }
+    /// Construct the `n` x `n` identity matrix
+    pub fn identity(n:usize) -> Matrix<T> {
+        let mut matrix = Matrix::new(n, n);
+        for i in 0..n {
+            matrix[(i, i)] = T::one();
+        }
+        return matrix;
+    }
+    /// Construct a matrix from `rows`, which must all have the same length
+    pub fn from_rows(rows:Vec<Vec<T>>) -> Result<Matrix<T>, Error> {
+        let cols = if !rows.is_empty() { rows[0].len() } else { 0 };
+        let n = rows.len();
+        let mut cells = Vec::with_capacity(n * cols);
+        for (r, row) in rows.into_iter().enumerate() {
+            if row.len() != cols {
+                return Err(Error::Input(format!("matrix row {} has {} entries, expecting {}", r + 1, row.len(), cols)));
+            }
+            cells.extend(row);
+        }
+        return Ok(Matrix { cells, rows:n, cols });
+    }
+    pub fn rows(&self) -> usize {
+        return self.rows;
+    }
+    pub fn cols(&self) -> usize {
+        return self.cols;
+    }
+    /// The entries of row `r`
+    pub fn row(&self, r:usize) -> &[T] {
+        return &self.cells[r * self.cols..(r + 1) * self.cols];
+    }
+    /// The entries of column `c`
+    pub fn column(&self, c:usize) -> Vec<T> {
+        return (0..self.rows).map(|r| self[(r, c)].clone()).collect();
+    }
+    pub fn swap_rows(&mut self, a:usize, b:usize) {
+        for c in 0..self.cols {
+            self.cells.swap(a * self.cols + c, b * self.cols + c);
+        }
+    }
+    /// Swap rows and columns
+    pub fn transpose(&self) -> Matrix<T> {
+        let mut result = Matrix::new(self.cols, self.rows);
+        for r in 0..self.rows {
+            for c in 0..self.cols {
+                result[(c, r)] = self[(r, c)].clone();
+            }
+        }
+        return result;
+    }
+    /// A copy of the matrix with `column` appended on the right
+    pub fn augment(&self, column:&[T]) -> Matrix<T> {
+        assert_eq!(column.len(), self.rows, "Augmenting {} rows with a column of {}", self.rows, column.len());
+        let mut cells = Vec::with_capacity(self.rows * (self.cols + 1));
+        for r in 0..self.rows {
+            cells.extend_from_slice(self.row(r));
+            cells.push(column[r].clone());
+        }
+        return Matrix { cells, rows:self.rows, cols:self.cols + 1 };
+    }
+    /// The product of the matrix with the column vector `x`
+    pub fn mul_vec(&self, x:&[T]) -> Vec<T> {
+        assert_eq!(x.len(), self.cols, "Multiplying {} columns by a vector of {}", self.cols, x.len());
+        let mut result = Vec::with_capacity(self.rows);
+        for r in 0..self.rows {
+            let mut sum = T::zero();
+            for c in 0..self.cols {
+                sum = sum + self[(r, c)].clone() * x[c].clone();
+            }
+            result.push(sum);
+        }
+        return result;
+    }
+}
+impl<T: Scalar + Div<Output=T>> Matrix<T> {
+    /// The determinant, or `None` if the matrix isn't square. Uses
+    /// fraction-free elimination (the Bareiss algorithm), so every division is
+    /// exact and integer matrices never leave the integers.
+    pub fn determinant(&self) -> Option<T> {
+        if self.rows != self.cols {
+            return None;
+        }
+        let n = self.rows;
+        let mut m = self.clone();
+        let mut sign = T::one();
+        let mut previous = T::one();
+        for k in 0..n {
+            if m[(k, k)].is_zero() {
+                match (k + 1..n).find(|&r| !m[(r, k)].is_zero()) {
+                    Some(r) => {
+                        m.swap_rows(k, r);
+                        sign = -sign;
+                    },
+                    None => return Some(T::zero())
+                }
+            }
+            for i in k + 1..n {
+                for j in k + 1..n {
+                    let value = m[(i, j)].clone() * m[(k, k)].clone() - m[(i, k)].clone() * m[(k, j)].clone();
+                    m[(i, j)] = value / previous.clone();
+                }
+            }
+            previous = m[(k, k)].clone();
+        }
+        if n == 0 {
+            return Some(T::one());
+        }
+        return Some(sign * m[(n - 1, n - 1)].clone());
+    }
+}
+impl<T: Field> Matrix<T> {
+    /// The reduced row echelon form, and the column of the pivot in each
+    /// non-zero row
+    pub fn rref(&self) -> (Matrix<T>, Vec<usize>) {
+        let mut m = self.clone();
+        let mut pivots = Vec::new();
+        for c in 0..self.cols {
+            let r = pivots.len();
+            if r == self.rows {
+                break;
+            }
+            let pivot_row = match (r..self.rows).find(|&i| !m[(i, c)].is_zero()) {
+                Some(i) => i,
+                None => continue
+            };
+            m.swap_rows(r, pivot_row);
+            // Scale the pivot to 1 and clear the rest of its column
+            let pivot = m[(r, c)].clone();
+            for j in c..self.cols {
+                m[(r, j)] = m[(r, j)].clone() / pivot.clone();
+            }
+            for i in 0..self.rows {
+                if i == r || m[(i, c)].is_zero() {
+                    continue;
+                }
+                let factor = m[(i, c)].clone();
+                for j in c..self.cols {
+                    m[(i, j)] = m[(i, j)].clone() - factor.clone() * m[(r, j)].clone();
+                }
+            }
+            pivots.push(c);
+        }
+        return (m, pivots);
+    }
+    pub fn rank(&self) -> usize {
+        return self.rref().1.len();
+    }
+    /// A basis of the null space, the vectors `x` with `Ax = 0`, with one
+    /// vector per non-pivot column
+    pub fn null_space(&self) -> Vec<Vec<T>> {
+        let (reduced, pivots) = self.rref();
+        return Matrix::basis(&reduced, &pivots, self.cols).0;
+    }
+    /// The null space basis of a reduced matrix with the given `pivots`,
+    /// considering only its first `cols` columns, and the free columns that
+    /// each basis vector corresponds to
+    fn basis(reduced:&Matrix<T>, pivots:&Vec<usize>, cols:usize) -> (Vec<Vec<T>>, Vec<usize>) {
+        let free:Vec<usize> = (0..cols).filter(|c| !pivots.contains(c)).collect();
+        let mut basis = Vec::new();
+        for &f in &free {
+            let mut v = vec![T::zero(); cols];
+            v[f] = T::one();
+            for (r, &p) in pivots.iter().enumerate() {
+                v[p] = -reduced[(r, f)].clone();
+            }
+            basis.push(v);
+        }
+        return (basis, free);
+    }
+    /// The inverse, or `None` if the matrix is not square or is singular
+    pub fn inverse(&self) -> Option<Matrix<T>> {
+        if self.rows != self.cols {
+            return None;
+        }
+        let n = self.rows;
+        // Reduce [A | I] to [I | A^-1]
+        let mut augmented = Matrix::new(n, 2 * n);
+        for r in 0..n {
+            for c in 0..n {
+                augmented[(r, c)] = self[(r, c)].clone();
+            }
+            augmented[(r, n + r)] = T::one();
+        }
+        let (reduced, pivots) = augmented.rref();
+        if pivots.len() < n || pivots[n - 1] >= n {
+            return None;
+        }
+        let mut inverse = Matrix::new(n, n);
+        for r in 0..n {
+            for c in 0..n {
+                inverse[(r, c)] = reduced[(r, n + c)].clone();
+            }
+        }
+        return Some(inverse);
+    }
+    /// Solve `Ax = b`
+    pub fn solve(&self, b:&[T]) -> SolutionSet<T> {
+        let (reduced, pivots) = self.augment(b).rref();
+        // A pivot in the augmented column is a row 0 = 1
+        if pivots.last() == Some(&self.cols) {
+            return SolutionSet::Inconsistent;
+        }
+        let mut particular = vec![T::zero(); self.cols];
+        for (r, &p) in pivots.iter().enumerate() {
+            particular[p] = reduced[(r, self.cols)].clone();
+        }
+        if pivots.len() == self.cols {
+            return SolutionSet::Unique(particular);
+        }
+        let (basis, free) = Matrix::basis(&reduced, &pivots, self.cols);
+        return SolutionSet::Infinite { particular, basis, free };
+    }
+}
+impl<T> std::ops::Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+    /// The entry at (row, column)
+    fn index(&self, (r, c):(usize, usize)) -> &T {
+        assert!(r < self.rows && c < self.cols, "Entry ({}, {}) outside {}x{} matrix", r, c, self.rows, self.cols);
+        return &self.cells[r * self.cols + c];
+    }
+}
+impl<T> std::ops::IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (r, c):(usize, usize)) -> &mut T {
+        assert!(r < self.rows && c < self.cols, "Entry ({}, {}) outside {}x{} matrix", r, c, self.rows, self.cols);
+        return &mut self.cells[r * self.cols + c];
+    }
+}
+impl<T: Scalar> Mul<&Matrix<T>> for &Matrix<T> {
+    type Output = Matrix<T>;
+    fn mul(self, rhs:&Matrix<T>) -> Matrix<T> {
+        assert_eq!(self.cols, rhs.rows, "Multiplying {}x{} by {}x{} matrix", self.rows, self.cols, rhs.rows, rhs.cols);
+        let mut result = Matrix::new(self.rows, rhs.cols);
+        for r in 0..self.rows {
+            for c in 0..rhs.cols {
+                let mut sum = T::zero();
+                for k in 0..self.cols {
+                    sum = sum + self[(r, k)].clone() * rhs[(k, c)].clone();
+                }
+                result[(r, c)] = sum;
+            }
+        }
+        return result;
+    }
+}
+impl<T: std::fmt::Display> std::fmt::Display for Matrix<T> {
+    fn fmt(&self, f: &mut  std::fmt::Formatter) ->  std::fmt::Result {
+        for r in 0..self.rows {
+            let row:Vec<String> = self.cells[r * self.cols..(r + 1) * self.cols].iter().map(|x| x.to_string()).collect();
+            writeln!(f, "{}", row.join(" "))?;
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rational(rows:Vec<Vec<i64>>) -> Matrix<Rational> {
+        return Matrix::from_rows(rows.into_iter().map(|row| row.into_iter().map(Rational::new_int).collect()).collect()).unwrap();
+    }
+    #[test]
+    fn rref_rank_and_null_space() {
+        let m = rational(vec![vec![1, 2, 1, 4], vec![2, 4, 0, 6], vec![1, 2, 2, 5]]);
+        let (reduced, pivots) = m.rref();
+        assert_eq!(reduced, rational(vec![vec![1, 2, 0, 3], vec![0, 0, 1, 1], vec![0, 0, 0, 0]]));
+        assert_eq!(pivots, vec![0, 2]);
+        assert_eq!(m.rank(), 2);
+        let basis = m.null_space();
+        assert_eq!(basis.len(), 2);
+        for v in &basis {
+            assert!(m.mul_vec(v).iter().all(|x| x.is_zero()));
+        }
+    }
+    #[test]
+    fn determinant_and_inverse() {
+        let integers = Matrix::from_rows(vec![vec![2i64, -3, 1], vec![2, 0, -1], vec![1, 4, 5]]).unwrap();
+        assert_eq!(integers.determinant(), Some(49));
+        assert_eq!(Matrix::from_rows(vec![vec![0i64, 1], vec![1, 0]]).unwrap().determinant(), Some(-1));
+        let m = rational(vec![vec![2, -3, 1], vec![2, 0, -1], vec![1, 4, 5]]);
+        assert_eq!(m.determinant(), Some(Rational::new_int(49)));
+        let inverse = m.inverse().unwrap();
+        assert_eq!(&m * &inverse, Matrix::identity(3));
+        assert_eq!(inverse[(0, 0)], Rational::new(4, 49));
+        assert_eq!(rational(vec![vec![1, 2], vec![2, 4]]).inverse(), None);
+        assert_eq!(rational(vec![vec![1, 2, 3]]).determinant(), None);
+    }
+    #[test]
+    fn solve_cases() {
+        let m = rational(vec![vec![1, 1], vec![1, -1]]);
+        let b = |v:Vec<i64>| v.into_iter().map(Rational::new_int).collect::<Vec<Rational>>();
+        assert_eq!(m.solve(&b(vec![3, 1])), SolutionSet::Unique(b(vec![2, 1])));
+        let m = rational(vec![vec![1, 1, 1], vec![0, 1, 2]]);
+        match m.solve(&b(vec![6, 5])) {
+            SolutionSet::Infinite { particular, basis, free } => {
+                assert_eq!(free, vec![2]);
+                assert_eq!(particular, b(vec![1, 5, 0]));
+                assert_eq!(basis, vec![b(vec![1, -2, 1])]);
+            },
+            other => panic!("expected infinitely many solutions, found {:?}", other)
+        }
+        let m = rational(vec![vec![1, 1], vec![2, 2]]);
+        assert_eq!(m.solve(&b(vec![1, 3])), SolutionSet::Inconsistent);
+    }
+    #[test]
+    fn gf2() {
+        let bits = |v:Vec<u8>| v.into_iter().map(|x| Gf2(x == 1)).collect::<Vec<Gf2>>();
+        let m = Matrix::from_rows(vec![bits(vec![1, 1, 0]), bits(vec![0, 1, 1]), bits(vec![1, 0, 1])]).unwrap();
+        assert_eq!(m.rank(), 2);
+        assert_eq!(m.null_space(), vec![bits(vec![1, 1, 1])]);
+        assert_eq!(m.solve(&bits(vec![1, 0, 0])), SolutionSet::Inconsistent);
+        assert_eq!(m.solve(&bits(vec![1, 1, 0])), SolutionSet::Infinite { particular:bits(vec![0, 1, 0]), basis:vec![bits(vec![1, 1, 1])], free:vec![2] });
+        assert!(Matrix::<Gf2>::from_rows(vec![bits(vec![1]), bits(vec![1, 0])]).is_err());
+    }
+}
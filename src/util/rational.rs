@@ -0,0 +1,371 @@
+use super::BigInt;
+use std::cmp::Ordering;
+
+/// Integer type that a `Rational` can be built on. The `checked_*` methods
+/// return `None` where the result would overflow.
+pub trait Integer: Clone + Ord + std::fmt::Display + std::fmt::Debug {
+    fn from_i64(n:i64) -> Self;
+    /// The value as an `i128`, or `None` if it is out of range
+    fn to_i128(&self) -> Option<i128>;
+    fn zero() -> Self {
+        return Self::from_i64(0);
+    }
+    fn one() -> Self {
+        return Self::from_i64(1);
+    }
+    fn is_zero(&self) -> bool {
+        return *self == Self::zero();
+    }
+    fn is_negative(&self) -> bool {
+        return *self < Self::zero();
+    }
+    fn checked_add(&self, other:&Self) -> Option<Self>;
+    fn checked_sub(&self, other:&Self) -> Option<Self>;
+    fn checked_mul(&self, other:&Self) -> Option<Self>;
+    fn checked_neg(&self) -> Option<Self>;
+    /// Exact division by a non-zero common factor
+    fn div_exact(&self, divisor:&Self) -> Self;
+    /// Quotient rounded down and non-negative remainder of dividing by a
+    /// positive `divisor`
+    fn div_rem_floor(&self, divisor:&Self) -> (Self, Self);
+    /// Greatest common divisor of the absolute values
+    fn gcd(&self, other:&Self) -> Self;
+}
+macro_rules! primitive_integer {
+    ($t:ty) => {
+        impl Integer for $t {
+            fn from_i64(n:i64) -> $t {
+                return n as $t;
+            }
+            fn to_i128(&self) -> Option<i128> {
+                return Some(*self as i128);
+            }
+            fn checked_add(&self, other:&$t) -> Option<$t> {
+                return <$t>::checked_add(*self, *other);
+            }
+            fn checked_sub(&self, other:&$t) -> Option<$t> {
+                return <$t>::checked_sub(*self, *other);
+            }
+            fn checked_mul(&self, other:&$t) -> Option<$t> {
+                return <$t>::checked_mul(*self, *other);
+            }
+            fn checked_neg(&self) -> Option<$t> {
+                return <$t>::checked_neg(*self);
+            }
+            fn div_exact(&self, divisor:&$t) -> $t {
+                return self / divisor;
+            }
+            fn div_rem_floor(&self, divisor:&$t) -> ($t, $t) {
+                return (self.div_euclid(*divisor), self.rem_euclid(*divisor));
+            }
+            fn gcd(&self, other:&$t) -> $t {
+                let (mut a, mut b) = (self.unsigned_abs(), other.unsigned_abs());
+                while b != 0 {
+                    (a, b) = (b, a % b);
+                }
+                // Only the gcd of MIN with itself or zero is out of range,
+                // and that wraps to MIN, which still divides exactly
+                return a as $t;
+            }
+        }
+    };
+}
+primitive_integer!(i64);
+primitive_integer!(i128);
+impl Integer for BigInt {
+    fn from_i64(n:i64) -> BigInt {
+        return BigInt::from(n);
+    }
+    fn to_i128(&self) -> Option<i128> {
+        return BigInt::to_i128(self);
+    }
+    fn is_zero(&self) -> bool {
+        return BigInt::is_zero(self);
+    }
+    fn is_negative(&self) -> bool {
+        return BigInt::is_negative(self);
+    }
+    fn checked_add(&self, other:&BigInt) -> Option<BigInt> {
+        return Some(self + other);
+    }
+    fn checked_sub(&self, other:&BigInt) -> Option<BigInt> {
+        return Some(self - other);
+    }
+    fn checked_mul(&self, other:&BigInt) -> Option<BigInt> {
+        return Some(self * other);
+    }
+    fn checked_neg(&self) -> Option<BigInt> {
+        return Some(-self);
+    }
+    fn div_exact(&self, divisor:&BigInt) -> BigInt {
+        return self / divisor;
+    }
+    fn div_rem_floor(&self, divisor:&BigInt) -> (BigInt, BigInt) {
+        let (quotient, remainder) = self.div_rem(divisor);
+        if remainder.is_negative() {
+            return (quotient - BigInt::from(1), remainder + divisor.clone());
+        }
+        return (quotient, remainder);
+    }
+    fn gcd(&self, other:&BigInt) -> BigInt {
+        return BigInt::gcd(self, other);
+    }
+}
+
+/// Exact rational number over the integer type `T`, always stored in lowest
+/// terms with a positive denominator. Arithmetic reduces common factors
+/// before multiplying to keep intermediate values small. The operators panic
+/// on overflow; the `checked_*` methods return `None` instead.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Rational<T: Integer = i64> {
+    pub num:T,
+    pub denom:T
+}
+impl<T: Integer> Rational<T> {
+    /// The rational `numerator`/`denominator` in lowest terms, or `None` if
+    /// the denominator is zero or normalising its sign overflows
+    pub fn checked_new(numerator:T, denominator:T) -> Option<Rational<T>> {
+        if denominator.is_zero() {
+            return None;
+        }
+        if numerator.is_zero() {
+            return Some(Rational::zero());
+        }
+        // Divide out gcd between numerator and denominator from both
+        let gcd = numerator.gcd(&denominator);
+        let num = numerator.div_exact(&gcd);
+        let denom = denominator.div_exact(&gcd);
+        // have denominator positive
+        if denom.is_negative() {
+            return Some(Rational { num:num.checked_neg()?, denom:denom.checked_neg()? });
+        }
+        return Some(Rational { num, denom });
+    }
+    pub fn new(numerator:T, denominator:T) -> Rational<T> {
+        assert!(!denominator.is_zero(), "{}/{} has denominator zero", numerator, denominator);
+        return Rational::checked_new(numerator.clone(), denominator.clone())
+            .unwrap_or_else(|| panic!("Rational overflow normalising {}/{}", numerator, denominator));
+    }
+    pub fn new_int(numerator:T) -> Rational<T> {
+        return Rational { num:numerator, denom:T::one() };
+    }
+    pub fn zero() -> Rational<T> {
+        return Rational::new_int(T::zero());
+    }
+    pub fn is_integer(&self) -> bool {
+        return self.denom == T::one();
+    }
+    /// Largest integer not greater than this
+    pub fn floor(&self) -> T {
+        return self.num.div_rem_floor(&self.denom).0;
+    }
+    /// Smallest integer not less than this. Panics on overflow.
+    pub fn ceil(&self) -> T {
+        return self.checked_ceil().expect("Rational ceiling overflow");
+    }
+    /// Smallest integer not less than this, or `None` on overflow
+    pub fn checked_ceil(&self) -> Option<T> {
+        let (quotient, remainder) = self.num.div_rem_floor(&self.denom);
+        if remainder.is_zero() {
+            return Some(quotient);
+        }
+        return quotient.checked_add(&T::one());
+    }
+    pub fn checked_neg(&self) -> Option<Rational<T>> {
+        return Some(Rational { num:self.num.checked_neg()?, denom:self.denom.clone() });
+    }
+    pub fn checked_add(&self, rhs:&Rational<T>) -> Option<Rational<T>> {
+        // a/b + c/d = (a(d/g) + c(b/g)) / (b/g)d for g = gcd(b, d). Any
+        // factor the sum shares with the denominator must divide g.
+        let g = self.denom.gcd(&rhs.denom);
+        let numerator = self.num.checked_mul(&rhs.denom.div_exact(&g))?.checked_add(&rhs.num.checked_mul(&self.denom.div_exact(&g))?)?;
+        if numerator.is_zero() {
+            return Some(Rational::zero());
+        }
+        let g2 = numerator.gcd(&g);
+        let denominator = self.denom.div_exact(&g).checked_mul(&rhs.denom.div_exact(&g2))?;
+        return Some(Rational { num:numerator.div_exact(&g2), denom:denominator });
+    }
+    pub fn checked_sub(&self, rhs:&Rational<T>) -> Option<Rational<T>> {
+        return self.checked_add(&rhs.checked_neg()?);
+    }
+    pub fn checked_mul(&self, rhs:&Rational<T>) -> Option<Rational<T>> {
+        // Cancel each numerator against the other denominator first, which
+        // leaves the product in lowest terms
+        let g1 = self.num.gcd(&rhs.denom);
+        let g2 = rhs.num.gcd(&self.denom);
+        let numerator = self.num.div_exact(&g1).checked_mul(&rhs.num.div_exact(&g2))?;
+        let denominator = self.denom.div_exact(&g2).checked_mul(&rhs.denom.div_exact(&g1))?;
+        return Some(Rational { num:numerator, denom:denominator });
+    }
+    /// Divide by `rhs`, returning `None` on overflow or division by zero
+    pub fn checked_div(&self, rhs:&Rational<T>) -> Option<Rational<T>> {
+        return self.checked_mul(&Rational::checked_new(rhs.denom.clone(), rhs.num.clone())?);
+    }
+}
+impl<T: Integer> From<T> for Rational<T> {
+    fn from(n:T) -> Rational<T> {
+        return Rational::new_int(n);
+    }
+}
+impl<T: Integer> std::fmt::Display for Rational<T> {
+    fn fmt(&self, f: &mut  std::fmt::Formatter) ->  std::fmt::Result {
+        if self.is_integer() {
+            write!(f,"{}", self.num)
+        } else {
+            write!(f,"{}/{}", self.num, self.denom)
+        }
+    }
+}
+impl<T: Integer> std::ops::Neg for Rational<T> {
+    type Output = Rational<T>;
+    fn neg(self) -> Rational<T> {
+        return self.checked_neg().unwrap_or_else(|| panic!("Rational overflow in -({})", self));
+    }
+}
+/// Implement an operator and its assigning form for `Rational` and integer
+/// right hand sides in terms of a checked method
+macro_rules! rational_op {
+    ($op:ident, $method:ident, $assign_op:ident, $assign_method:ident, $checked:ident, $symbol:literal) => {
+        impl<T: Integer> std::ops::$op<Rational<T>> for Rational<T> {
+            type Output = Rational<T>;
+            fn $method(self, rhs:Rational<T>) -> Rational<T> {
+                return self.$checked(&rhs).unwrap_or_else(|| panic!("Rational overflow or division by zero in {} {} {}", self, $symbol, rhs));
+            }
+        }
+        impl<T: Integer> std::ops::$op<T> for Rational<T> {
+            type Output = Rational<T>;
+            fn $method(self, rhs:T) -> Rational<T> {
+                return std::ops::$op::$method(self, Rational::new_int(rhs));
+            }
+        }
+        impl<T: Integer> std::ops::$assign_op<Rational<T>> for Rational<T> {
+            fn $assign_method(&mut self, rhs:Rational<T>) {
+                *self = std::ops::$op::$method(self.clone(), rhs);
+            }
+        }
+        impl<T: Integer> std::ops::$assign_op<T> for Rational<T> {
+            fn $assign_method(&mut self, rhs:T) {
+                *self = std::ops::$op::$method(self.clone(), rhs);
+            }
+        }
+    };
+}
+rational_op!(Add, add, AddAssign, add_assign, checked_add, "+");
+rational_op!(Sub, sub, SubAssign, sub_assign, checked_sub, "-");
+rational_op!(Mul, mul, MulAssign, mul_assign, checked_mul, "*");
+rational_op!(Div, div, DivAssign, div_assign, checked_div, "/");
+impl<T: Integer> std::cmp::Ord for Rational<T> {
+    /// Compares by continued fraction expansion rather than cross
+    /// multiplying, so no intermediate value exceeds the inputs
+    fn cmp(&self, other:&Rational<T>) -> Ordering {
+        let (mut a, mut b) = (self.num.clone(), self.denom.clone());
+        let (mut c, mut d) = (other.num.clone(), other.denom.clone());
+        // Each step compares the reciprocals of the fractional parts, which
+        // reverses the order
+        let mut reversed = false;
+        loop {
+            let (q1, r1) = a.div_rem_floor(&b);
+            let (q2, r2) = c.div_rem_floor(&d);
+            let order = match (q1.cmp(&q2), r1.is_zero(), r2.is_zero()) {
+                (Ordering::Equal, true, true) => Ordering::Equal,
+                (Ordering::Equal, true, false) => Ordering::Less,
+                (Ordering::Equal, false, true) => Ordering::Greater,
+                (Ordering::Equal, false, false) => {
+                    // a/b and c/d have equal integer parts, so compare
+                    // r1/b with r2/d, or equivalently b/r1 with d/r2
+                    // in reverse
+                    (a, b, c, d) = (b, r1, d, r2);
+                    reversed = !reversed;
+                    continue;
+                },
+                (order, _, _) => order
+            };
+            return if reversed { order.reverse() } else { order };
+        }
+    }
+}
+impl<T: Integer> std::cmp::PartialOrd<Rational<T>> for Rational<T> {
+    fn partial_cmp(&self, other:&Rational<T>) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+impl<T: Integer> std::cmp::PartialOrd<T> for Rational<T> {
+    fn partial_cmp(&self, other:&T) -> Option<Ordering> {
+        return Some(self.cmp(&Rational::new_int(other.clone())));
+    }
+}
+impl<T: Integer> std::cmp::PartialEq<T> for Rational<T> {
+    fn eq(&self, other:&T) -> bool {
+        return self.is_integer() && self.num == *other;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rational_arithmetic() {
+        let half:Rational = Rational::new(2, 4);
+        assert_eq!((half.num, half.denom), (1, 2));
+        let negative:Rational = Rational::new(3, -6);
+        assert_eq!((negative.num, negative.denom), (-1, 2));
+        assert!(half + Rational::new(1, 3) == Rational::new(5, 6));
+        assert!(half * 4 == 2);
+        assert!(negative < half);
+        assert_eq!(Rational::<i64>::new(7, 3).to_string(), "7/3");
+        assert_eq!(half - half, Rational::zero());
+    }
+    #[test]
+    fn ordering() {
+        let values:Vec<Rational> = [(-7, 2), (-3, 1), (-1, 3), (0, 1), (1, 4), (2, 7), (1, 3), (5, 3), (2, 1)].iter().map(|&(n, d)| Rational::new(n, d)).collect();
+        for i in 0..values.len() {
+            for j in 0..values.len() {
+                assert_eq!(values[i].cmp(&values[j]), i.cmp(&j), "{} vs {}", values[i], values[j]);
+            }
+        }
+        // Cross multiplying these would overflow
+        let big = Rational::new(i64::MAX - 1, i64::MAX);
+        let bigger = Rational::new(i64::MAX, i64::MAX - 2);
+        assert!(big < bigger);
+        assert!(big < 1 && bigger > 1);
+    }
+    #[test]
+    fn rounding() {
+        let cases = [((7, 2), 3, 4), ((-7, 2), -4, -3), ((6, 3), 2, 2), ((-1, 3), -1, 0)];
+        for ((n, d), floor, ceil) in cases {
+            let x:Rational = Rational::new(n, d);
+            assert_eq!((x.floor(), x.ceil()), (floor, ceil), "{}", x);
+        }
+    }
+    #[test]
+    fn reduces_before_multiplying() {
+        let a = Rational::new(i64::MAX, 3);
+        let b = Rational::new(3, i64::MAX);
+        assert_eq!(a * b, Rational::new_int(1));
+        assert_eq!(Rational::new(1, i64::MAX - 1) + Rational::new(1, i64::MAX - 1), Rational::new(1, (i64::MAX - 1) / 2));
+    }
+    #[test]
+    fn checked_overflow() {
+        let big = Rational::new_int(i64::MAX);
+        assert_eq!(big.checked_add(&Rational::new_int(1)), None);
+        assert_eq!(big.checked_mul(&Rational::new(2, 3)), None);
+        assert_eq!(big.checked_div(&Rational::zero()), None);
+        assert_eq!(Rational::new_int(i64::MIN).checked_neg(), None);
+        let wide:Rational<i128> = Rational::new_int(i64::MAX as i128);
+        assert_eq!(wide.checked_add(&Rational::new_int(1)), Some(Rational::new_int(i64::MAX as i128 + 1)));
+    }
+    #[test]
+    fn big_rationals() {
+        let third:Rational<BigInt> = Rational::new(BigInt::from(1), BigInt::from(3));
+        let mut sum = Rational::zero();
+        for _ in 0..3 {
+            sum += third.clone();
+        }
+        assert_eq!(sum, BigInt::from(1));
+        let huge = Rational::new_int(BigInt::from(i128::MAX)) * BigInt::from(i128::MAX);
+        assert!(huge > BigInt::from(i128::MAX));
+        assert_eq!((huge / BigInt::from(i128::MAX)).to_string(), i128::MAX.to_string());
+    }
+}
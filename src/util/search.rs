@@ -0,0 +1,237 @@
+use super::{Grid, Vec2};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// A path found by a search
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path<S, C> {
+    /// States along the path from the start to the goal, inclusive
+    pub states:Vec<S>,
+    /// Total cost of the path. For `bfs` this is the number of steps.
+    pub cost:C,
+    /// Number of states expanded by the search before reaching the goal
+    pub visited:usize
+}
+
+/// Follow `parents` back from `end` to the state with no parent
+fn reconstruct<S: Hash + Eq + Clone>(parents:&HashMap<S, S>, end:&S) -> Vec<S> {
+    let mut states = vec![end.clone()];
+    while let Some(parent) = parents.get(states.last().unwrap()) {
+        states.push(parent.clone());
+    }
+    states.reverse();
+    return states;
+}
+
+/// Breadth first search from `start` for the nearest state satisfying
+/// `is_goal`, where `neighbors` gives the states one step from a state.
+/// Returns `None` if no goal state is reachable.
+pub fn bfs<S, N, I, G>(start:S, mut neighbors:N, mut is_goal:G) -> Option<Path<S, usize>>
+where S: Hash + Eq + Clone, N: FnMut(&S) -> I, I: IntoIterator<Item=S>, G: FnMut(&S) -> bool {
+    let mut parents:HashMap<S, S> = HashMap::new();
+    let mut steps:HashMap<S, usize> = HashMap::from([(start.clone(), 0)]);
+    let mut queue = VecDeque::from([start]);
+    let mut visited = 0;
+    while let Some(state) = queue.pop_front() {
+        visited += 1;
+        let state_steps = steps[&state];
+        if is_goal(&state) {
+            return Some(Path { states:reconstruct(&parents, &state), cost:state_steps, visited });
+        }
+        for next in neighbors(&state) {
+            if !steps.contains_key(&next) {
+                steps.insert(next.clone(), state_steps + 1);
+                parents.insert(next.clone(), state.clone());
+                queue.push_back(next);
+            }
+        }
+    }
+    return None;
+}
+
+/// Breadth first search from `start` over every reachable state, returning
+/// the number of steps to each. The number of states visited is the length
+/// of the result.
+pub fn bfs_all<S, N, I>(start:S, mut neighbors:N) -> HashMap<S, usize>
+where S: Hash + Eq + Clone, N: FnMut(&S) -> I, I: IntoIterator<Item=S> {
+    let mut steps:HashMap<S, usize> = HashMap::from([(start.clone(), 0)]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(state) = queue.pop_front() {
+        let state_steps = steps[&state];
+        for next in neighbors(&state) {
+            if !steps.contains_key(&next) {
+                steps.insert(next.clone(), state_steps + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+    return steps;
+}
+
+/// Costs and search tree of a best first search
+struct Frontier<S, C> {
+    /// Index of each state seen in `nodes`
+    index:HashMap<S, usize>,
+    /// (state, best known cost, parent node, expanded) for each state seen
+    nodes:Vec<(S, C, Option<usize>, bool)>,
+    /// Nodes to expand as (cost + heuristic, node), lowest first
+    queue:BinaryHeap<Reverse<(C, usize)>>
+}
+impl<S: Hash + Eq + Clone, C: Copy + Ord + Add<Output=C> + Default> Frontier<S, C> {
+    /// Search from `start`, expanding states in order of cost plus
+    /// `heuristic` until `stop` returns true for an expanded state. Returns
+    /// the stopping node and the number of states expanded.
+    fn search<N, I, H, G>(start:S, neighbors:&mut N, heuristic:&mut H, stop:&mut G) -> (Frontier<S, C>, Option<usize>, usize)
+    where N: FnMut(&S) -> I, I: IntoIterator<Item=(S, C)>, H: FnMut(&S) -> C, G: FnMut(&S) -> bool {
+        let start_priority = heuristic(&start);
+        let mut frontier = Frontier {
+            index:HashMap::from([(start.clone(), 0)]),
+            nodes:vec![(start, C::default(), None, false)],
+            queue:BinaryHeap::from([Reverse((start_priority, 0))])
+        };
+        let mut visited = 0;
+        while let Some(Reverse((_, node))) = frontier.queue.pop() {
+            // Skip stale queue entries for states already expanded at a lower
+            // cost
+            if frontier.nodes[node].3 {
+                continue;
+            }
+            frontier.nodes[node].3 = true;
+            visited += 1;
+            let state = frontier.nodes[node].0.clone();
+            let cost = frontier.nodes[node].1;
+            if stop(&state) {
+                return (frontier, Some(node), visited);
+            }
+            for (next, step_cost) in neighbors(&state) {
+                let next_cost = cost + step_cost;
+                let next_node = match frontier.index.get(&next) {
+                    Some(&n) => {
+                        if frontier.nodes[n].3 || frontier.nodes[n].1 <= next_cost {
+                            continue;
+                        }
+                        frontier.nodes[n].1 = next_cost;
+                        frontier.nodes[n].2 = Some(node);
+                        n
+                    },
+                    None => {
+                        frontier.index.insert(next.clone(), frontier.nodes.len());
+                        frontier.nodes.push((next.clone(), next_cost, Some(node), false));
+                        frontier.nodes.len() - 1
+                    }
+                };
+                frontier.queue.push(Reverse((next_cost + heuristic(&next), next_node)));
+            }
+        }
+        return (frontier, None, visited);
+    }
+    /// The path from the start to `node`
+    fn path(&self, node:usize, visited:usize) -> Path<S, C> {
+        let mut states = Vec::new();
+        let mut curr = Some(node);
+        while let Some(n) = curr {
+            states.push(self.nodes[n].0.clone());
+            curr = self.nodes[n].2;
+        }
+        states.reverse();
+        return Path { states, cost:self.nodes[node].1, visited };
+    }
+}
+
+/// Dijkstra's algorithm from `start` for the cheapest state satisfying
+/// `is_goal`, where `neighbors` gives the states one step from a state with
+/// the (non-negative) cost of each step. Returns `None` if no goal state is
+/// reachable.
+pub fn dijkstra<S, C, N, I, G>(start:S, mut neighbors:N, mut is_goal:G) -> Option<Path<S, C>>
+where S: Hash + Eq + Clone, C: Copy + Ord + Add<Output=C> + Default, N: FnMut(&S) -> I, I: IntoIterator<Item=(S, C)>, G: FnMut(&S) -> bool {
+    let (frontier, goal, visited) = Frontier::search(start, &mut neighbors, &mut |_:&S| C::default(), &mut is_goal);
+    return goal.map(|node| frontier.path(node, visited));
+}
+
+/// Dijkstra's algorithm from `start` over every reachable state, returning
+/// the cheapest cost to each
+pub fn dijkstra_all<S, C, N, I>(start:S, mut neighbors:N) -> HashMap<S, C>
+where S: Hash + Eq + Clone, C: Copy + Ord + Add<Output=C> + Default, N: FnMut(&S) -> I, I: IntoIterator<Item=(S, C)> {
+    let (frontier, _, _) = Frontier::search(start, &mut neighbors, &mut |_:&S| C::default(), &mut |_:&S| false);
+    return frontier.nodes.into_iter().map(|(state, cost, _, _)| (state, cost)).collect();
+}
+
+/// A* search from `start` for the cheapest state satisfying `is_goal`,
+/// guided by `heuristic`, an estimate of the remaining cost to a goal. The
+/// heuristic must never overestimate and must be consistent (it decreases by
+/// at most the step cost along each step) for the path found to be the
+/// cheapest. Returns `None` if no goal state is reachable.
+pub fn astar<S, C, N, I, H, G>(start:S, mut neighbors:N, mut heuristic:H, mut is_goal:G) -> Option<Path<S, C>>
+where S: Hash + Eq + Clone, C: Copy + Ord + Add<Output=C> + Default, N: FnMut(&S) -> I, I: IntoIterator<Item=(S, C)>, H: FnMut(&S) -> C, G: FnMut(&S) -> bool {
+    let (frontier, goal, visited) = Frontier::search(start, &mut neighbors, &mut heuristic, &mut is_goal);
+    return goal.map(|node| frontier.path(node, visited));
+}
+
+/// Neighbor function for searching `grid` in orthogonal steps between cells
+/// for which `passable` is true
+pub fn grid_steps<'a, T>(grid:&'a Grid<T>, passable:impl Fn(&T) -> bool + 'a) -> impl FnMut(&Vec2) -> Vec<Vec2> + 'a {
+    return move |pos:&Vec2| grid.neighbors4(*pos).filter(|n| passable(&grid[*n])).collect();
+}
+
+/// Neighbor function for searching `grid` in orthogonal steps between cells
+/// for which `passable` is true, with each step costing 1. For use with
+/// `dijkstra` and `astar`.
+pub fn grid_unit_steps<'a, T>(grid:&'a Grid<T>, passable:impl Fn(&T) -> bool + 'a) -> impl FnMut(&Vec2) -> Vec<(Vec2, usize)> + 'a {
+    return move |pos:&Vec2| grid.neighbors4(*pos).filter(|n| passable(&grid[*n])).map(|n| (n, 1)).collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn maze() -> Grid<char> {
+        let lines:Vec<String> = ["S..#....", ".#.#.##.", ".#...#..", ".####.#.", "......#E"].iter().map(|l| l.to_string()).collect();
+        return Grid::from_lines(&lines).unwrap();
+    }
+    #[test]
+    fn grid_searches_agree() {
+        let grid = maze();
+        let start = grid.find(&'S').unwrap();
+        let end = grid.find(&'E').unwrap();
+        let path = bfs(start, grid_steps(&grid, |&c| c != '#'), |p| *p == end).unwrap();
+        assert_eq!(path.cost, 15);
+        assert_eq!(path.states.len(), 16);
+        assert_eq!((path.states[0], *path.states.last().unwrap()), (start, end));
+        for i in 1..path.states.len() {
+            assert_eq!(path.states[i].manhattan(path.states[i - 1]), 1);
+        }
+        let shortest = dijkstra(start, grid_unit_steps(&grid, |&c| c != '#'), |p| *p == end).unwrap();
+        assert_eq!(shortest.cost, 15);
+        let guided = astar(start, grid_unit_steps(&grid, |&c| c != '#'), |p| p.manhattan(end) as usize, |p| *p == end).unwrap();
+        assert_eq!(guided.cost, 15);
+        assert!(guided.visited <= shortest.visited);
+        let steps = bfs_all(start, grid_steps(&grid, |&c| c != '#'));
+        assert_eq!(steps[&end], 15);
+        assert_eq!(steps.len(), grid.find_all(&'.').len() + 2);
+    }
+    #[test]
+    fn unreachable() {
+        let grid = maze();
+        let start = grid.find(&'S').unwrap();
+        assert!(bfs(start, grid_steps(&grid, |&c| c != '#'), |p| *p == Vec2::new(3, 0)).is_none());
+    }
+    #[test]
+    fn weighted_graph() {
+        let edges:HashMap<&str, Vec<(&str, u32)>> = HashMap::from([
+            ("a", vec![("b", 7), ("c", 9), ("f", 14)]),
+            ("b", vec![("c", 10), ("d", 15)]),
+            ("c", vec![("d", 11), ("f", 2)]),
+            ("d", vec![("e", 6)]),
+            ("f", vec![("e", 9)])
+        ]);
+        let neighbors = |s:&&str| edges.get(s).cloned().unwrap_or_default();
+        let path = dijkstra("a", neighbors, |s| *s == "e").unwrap();
+        assert_eq!(path.cost, 20);
+        assert_eq!(path.states, vec!["a", "c", "f", "e"]);
+        let costs = dijkstra_all("a", neighbors);
+        assert_eq!(costs["d"], 20);
+        assert_eq!(costs.len(), 6);
+    }
+}
@@ -0,0 +1,103 @@
+/// Disjoint-set forest over the elements `0..n` with path compression and 
+/// union by rank
+#[derive(Debug, Clone)]
+pub struct UnionFind {
+    parent:Vec<usize>,
+    rank:Vec<usize>,
+    /// Number of elements in each set, valid for root elements only
+    size:Vec<usize>,
+    components:usize
+}
+impl UnionFind {
+    /// Construct `n` singleton sets
+    pub fn new(n:usize) -> UnionFind {
+        return UnionFind { parent:(0..n).collect(), rank:vec![0;n], size:vec![1;n], components:n };
+    }
+    /// Number of elements
+    pub fn len(&self) -> usize {
+        return self.parent.len();
+    }
+    pub fn is_empty(&self) -> bool {
+        return self.parent.is_empty();
+    }
+    /// Number of disjoint sets
+    pub fn components(&self) -> usize {
+        return self.components;
+    }
+    /// Find the root element of the set containing `x`, compressing the path
+    /// to it
+    pub fn find(&mut self, x:usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        let mut curr = x;
+        while self.parent[curr] != root {
+            let next = self.parent[curr];
+            self.parent[curr] = root;
+            curr = next;
+        }
+        return root;
+    }
+    /// Merge the sets containing `a` and `b`. Returns false if they were 
+    /// already in the same set.
+    pub fn union(&mut self, a:usize, b:usize) -> bool {
+        let mut a_root = self.find(a);
+        let mut b_root = self.find(b);
+        if a_root == b_root {
+            return false;
+        }
+        // Attach the shallower tree below the deeper one
+        if self.rank[a_root] < self.rank[b_root] {
+            (a_root, b_root) = (b_root, a_root);
+        }
+        self.parent[b_root] = a_root;
+        self.size[a_root] += self.size[b_root];
+        if self.rank[a_root] == self.rank[b_root] {
+            self.rank[a_root] += 1;
+        }
+        self.components -= 1;
+        return true;
+    }
+    /// Test if `a` and `b` are in the same set
+    pub fn connected(&mut self, a:usize, b:usize) -> bool {
+        return self.find(a) == self.find(b);
+    }
+    /// Number of elements in the set containing `x`
+    pub fn size(&mut self, x:usize) -> usize {
+        let root = self.find(x);
+        return self.size[root];
+    }
+    /// Sizes of all disjoint sets, in no particular order
+    pub fn component_sizes(&self) -> Vec<usize> {
+        let mut sizes = Vec::new();
+        for i in 0..self.parent.len() {
+            if self.parent[i] == i {
+                sizes.push(self.size[i]);
+            }
+        }
+        return sizes;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn union_find() {
+        let mut sets = UnionFind::new(6);
+        assert_eq!(sets.components(), 6);
+        assert!(sets.union(0, 1));
+        assert!(sets.union(2, 3));
+        assert!(sets.union(1, 3));
+        assert!(!sets.union(0, 2));
+        assert!(sets.connected(0, 3));
+        assert!(!sets.connected(0, 4));
+        assert_eq!(sets.size(2), 4);
+        assert_eq!(sets.components(), 3);
+        let mut sizes = sets.component_sizes();
+        sizes.sort();
+        assert_eq!(sizes, vec![1, 1, 4]);
+    }
+}